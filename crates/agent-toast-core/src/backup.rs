@@ -0,0 +1,358 @@
+//! Timestamped backups of the settings files agent-toast rewrites
+//! (hook scopes' `settings.json`, `~/.codex/config.toml`).
+//!
+//! Before each write the current file is copied to
+//! `~/.claude/agent-toast-backups/<path hash>/<file name>.<UTC timestamp>`,
+//! keeping the newest [`KEEP`] copies per file. Backups of a project's
+//! `.claude/` files stay in the user's home too, so they never land in the
+//! repository. [`list`] and [`restore`] back the `agent-toast-send restore`
+//! command and the desktop's restore button.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Backups kept per file; older ones are pruned after each backup.
+pub const KEEP: usize = 10;
+
+/// Directory under `~/.claude` holding the backups, one subdirectory per
+/// backed-up file.
+pub const DIR_NAME: &str = "agent-toast-backups";
+
+/// One backup of a file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// UTC time the backup was taken, e.g. `2026-10-16T09:30:05.120Z`.
+    pub created: String,
+}
+
+/// Copy `path` into its backup directory under `home`, unless it doesn't
+/// exist or the newest backup already holds the same bytes. Returns the new
+/// backup.
+pub fn backup(home: &Path, path: &Path) -> io::Result<Option<PathBuf>> {
+    let current = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let newest = stamped(home, path).into_iter().next();
+    if let Some((_, newest)) = &newest {
+        if std::fs::read(newest).is_ok_and(|b| b == current) {
+            return Ok(None);
+        }
+    }
+    let dir = backup_dir(home, path);
+    std::fs::create_dir_all(&dir)?;
+    // Always after the newest backup, even within one millisecond or if the
+    // clock went back, so the newest backup sorts first.
+    let mut millis = now_millis().max(newest.map_or(0, |(last, _)| last + 1));
+    let target = loop {
+        let candidate = dir.join(format!("{}.{}", file_name(path), stamp(millis)));
+        if !candidate.exists() {
+            break candidate;
+        }
+        millis += 1;
+    };
+    std::fs::write(&target, current)?;
+    for old in list(home, path).into_iter().skip(KEEP) {
+        let _ = std::fs::remove_file(old.path);
+    }
+    Ok(Some(target))
+}
+
+/// Back up `path` (see [`backup`]), then write `contents` to it, creating
+/// the parent directory if needed.
+pub fn write_with_backup(home: &Path, path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    backup(home, path)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+}
+
+/// Backups of `path`, newest first.
+pub fn list(home: &Path, path: &Path) -> Vec<Backup> {
+    stamped(home, path)
+        .into_iter()
+        .map(|(millis, path)| Backup {
+            path,
+            created: rfc3339(millis),
+        })
+        .collect()
+}
+
+/// Backup files of `path` with their timestamps (Unix ms), newest first.
+fn stamped(home: &Path, path: &Path) -> Vec<(u64, PathBuf)> {
+    let prefix = format!("{}.", file_name(path));
+    let Ok(entries) = std::fs::read_dir(backup_dir(home, path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(u64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            Some((parse_stamp(name.strip_prefix(&prefix)?)?, e.path()))
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.0));
+    backups
+}
+
+/// Put `backup` (one of [`list`]`(home, path)`) back in place of `path`.
+/// The current file is backed up first, so a restore can itself be undone.
+pub fn restore(home: &Path, path: &Path, backup: &Path) -> io::Result<()> {
+    if !stamped(home, path).iter().any(|(_, b)| b == backup) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a backup of {}", backup.display(), path.display()),
+        ));
+    }
+    let contents = std::fs::read(backup)?;
+    write_with_backup(home, path, contents)
+}
+
+fn backup_dir(home: &Path, path: &Path) -> PathBuf {
+    home.join(".claude").join(DIR_NAME).join(path_key(path))
+}
+
+/// FNV-1a hash of the absolute `path`, in hex: keeps files of the same name
+/// (every project's `settings.json`) apart, and is stable across builds.
+fn path_key(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let hash = path
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, &b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// File-name-safe UTC timestamp: `20261016T093005120Z`. Sorts by time.
+fn stamp(millis: u64) -> String {
+    let (y, mo, d, h, mi, s, ms) = utc_fields(millis);
+    format!("{y:04}{mo:02}{d:02}T{h:02}{mi:02}{s:02}{ms:03}Z")
+}
+
+/// `2026-10-16T09:30:05.120Z`
+fn rfc3339(millis: u64) -> String {
+    let (y, mo, d, h, mi, s, ms) = utc_fields(millis);
+    format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}.{ms:03}Z")
+}
+
+/// (year, month, day, hour, minute, second, millisecond) in UTC.
+fn utc_fields(millis: u64) -> (i64, u32, u32, u64, u64, u64, u64) {
+    let secs = millis / 1000;
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    (
+        y,
+        m,
+        d,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60,
+        millis % 1000,
+    )
+}
+
+/// Unix milliseconds of a [`stamp`], or `None` for names that aren't
+/// backups.
+fn parse_stamp(s: &str) -> Option<u64> {
+    let b = s.as_bytes();
+    if b.len() != 19 || b[8] != b'T' || b[18] != b'Z' {
+        return None;
+    }
+    let num = |r: std::ops::Range<usize>| -> Option<u64> {
+        let digits = b.get(r)?;
+        digits
+            .iter()
+            .all(u8::is_ascii_digit)
+            .then(|| digits.iter().fold(0, |n, d| n * 10 + u64::from(d - b'0')))
+    };
+    let days = days_from_civil(num(0..4)? as i64, num(4..6)? as u32, num(6..8)? as u32);
+    let secs =
+        u64::try_from(days).ok()? * 86_400 + num(9..11)? * 3600 + num(11..13)? * 60 + num(13..15)?;
+    Some(secs * 1000 + num(15..18)?)
+}
+
+/// (year, month, day) to days since 1970-01-01; inverse of
+/// [`civil_from_days`].
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = i64::from(m);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(d) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Days since 1970-01-01 to (year, month, day), proleptic Gregorian.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scratch directory in the temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("agent-toast-backup-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn stamp_formats_utc() {
+        assert_eq!(stamp(0), "19700101T000000000Z");
+        // 2026-10-16T09:30:05.120Z
+        assert_eq!(stamp(1_792_143_005_120), "20261016T093005120Z");
+        assert_eq!(rfc3339(1_792_143_005_120), "2026-10-16T09:30:05.120Z");
+        assert_eq!(parse_stamp("20261016T093005120Z"), Some(1_792_143_005_120));
+        assert_eq!(parse_stamp("19700101T000000000Z"), Some(0));
+        assert_eq!(parse_stamp("20240229T235959999Z"), Some(1_709_251_199_999));
+        assert_eq!(parse_stamp("20261016T093005120Z.tmp"), None);
+        assert_eq!(parse_stamp("2026101xT093005120Z"), None);
+    }
+
+    #[test]
+    fn backup_skips_missing_file_and_unchanged_content() {
+        let tmp = TempDir::new("skip");
+        let home = tmp.path();
+        let path = home.join("settings.json");
+        assert_eq!(backup(home, &path).unwrap(), None);
+
+        std::fs::write(&path, "{}").unwrap();
+        let first = backup(home, &path).unwrap().expect("first backup");
+        assert!(first.starts_with(home.join(".claude").join(DIR_NAME)));
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "{}");
+        assert_eq!(
+            backup(home, &path).unwrap(),
+            None,
+            "same bytes as the newest"
+        );
+        assert_eq!(list(home, &path).len(), 1);
+    }
+
+    #[test]
+    fn rotation_keeps_newest() {
+        let tmp = TempDir::new("rotate");
+        let home = tmp.path();
+        let path = home.join("config.toml");
+        for i in 0..KEEP + 3 {
+            std::fs::write(&path, format!("v = {i}")).unwrap();
+            backup(home, &path).unwrap();
+        }
+        let backups = list(home, &path);
+        assert_eq!(backups.len(), KEEP);
+        assert_eq!(
+            std::fs::read_to_string(&backups[0].path).unwrap(),
+            format!("v = {}", KEEP + 2)
+        );
+        // Other files' backups are not counted.
+        assert!(list(home, &home.join("settings.json")).is_empty());
+    }
+
+    #[test]
+    fn project_files_are_backed_up_under_home() {
+        let tmp = TempDir::new("project");
+        let home = tmp.path().join("home");
+        let app = tmp.path().join("app").join(".claude").join("settings.json");
+        let lib = tmp.path().join("lib").join(".claude").join("settings.json");
+        for path in [&app, &lib] {
+            write_with_backup(&home, path, "{}").unwrap();
+            write_with_backup(&home, path, path.to_string_lossy().as_bytes()).unwrap();
+        }
+
+        // Nothing is written inside the projects.
+        for project in ["app", "lib"] {
+            let claude = tmp.path().join(project).join(".claude");
+            let names: Vec<_> = std::fs::read_dir(claude)
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect();
+            assert_eq!(names, ["settings.json"], "{project}");
+        }
+        // Same file name, separate histories.
+        let (app_backups, lib_backups) = (list(&home, &app), list(&home, &lib));
+        assert_eq!((app_backups.len(), lib_backups.len()), (1, 1));
+        assert_ne!(app_backups[0].path.parent(), lib_backups[0].path.parent());
+        assert!(app_backups[0]
+            .path
+            .starts_with(home.join(".claude").join(DIR_NAME)));
+        assert!(restore(&home, &app, &lib_backups[0].path).is_err());
+    }
+
+    #[test]
+    fn write_then_restore_round_trips() {
+        let tmp = TempDir::new("restore");
+        let home = tmp.path();
+        let path = home.join(".claude").join("settings.json");
+        write_with_backup(home, &path, "{\"v\": 1}").unwrap();
+        assert!(list(home, &path).is_empty(), "nothing to back up yet");
+        write_with_backup(home, &path, "{\"v\": 2}").unwrap();
+
+        let backups = list(home, &path);
+        assert_eq!(backups.len(), 1);
+        restore(home, &path, &backups[0].path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"v\": 1}");
+        // The restore backed up v2, so it can be undone.
+        assert_eq!(
+            std::fs::read_to_string(&list(home, &path)[0].path).unwrap(),
+            "{\"v\": 2}"
+        );
+
+        let stray = home.join("elsewhere.json");
+        std::fs::write(&stray, "{}").unwrap();
+        assert!(restore(home, &path, &stray).is_err());
+
+        // Inside the backup directory, but not a backup of `path`.
+        let dir = backup_dir(home, &path);
+        for name in ["notes.txt", "other.json.20261016T093005120Z"] {
+            let stray = dir.join(name);
+            std::fs::write(&stray, "{}").unwrap();
+            assert!(restore(home, &path, &stray).is_err(), "{name}");
+        }
+    }
+}
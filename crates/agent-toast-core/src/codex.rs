@@ -0,0 +1,193 @@
+//! Codex CLI `notify` payloads.
+//!
+//! Codex runs the configured `notify` program with one JSON argument after
+//! each turn:
+//!
+//! ```json
+//! {
+//!   "type": "agent-turn-complete",
+//!   "thread-id": "…",
+//!   "turn-id": "…",
+//!   "cwd": "/home/me/proj",
+//!   "input-messages": ["Rename foo to bar"],
+//!   "last-assistant-message": "Renamed and `cargo build` passes."
+//! }
+//! ```
+//!
+//! Shared by the desktop's `--codex` mode (local pipe) and
+//! `agent-toast-send --codex` (HTTP), so both turn the same payload into the
+//! same [`NotifyRequest`].
+
+use crate::dynamic::{normalize_message, Redactor};
+use crate::wire::{HookContext, NotifyRequest, Priority, SCHEMA_VERSION};
+use serde::Deserialize;
+
+/// `source` of every Codex notification.
+pub const SOURCE: &str = "codex";
+
+/// `type` assumed when the payload doesn't carry one.
+pub const DEFAULT_TYPE: &str = "agent-turn-complete";
+
+/// One Codex `notify` payload. Unknown keys are ignored and every field is
+/// optional, so older and newer Codex versions both parse.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CodexNotification {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub thread_id: Option<String>,
+    pub turn_id: Option<String>,
+    pub cwd: Option<String>,
+    /// User messages that started the turn, oldest first.
+    pub input_messages: Vec<String>,
+    pub last_assistant_message: Option<String>,
+}
+
+fn non_blank(s: &Option<String>) -> Option<&str> {
+    s.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+impl CodexNotification {
+    /// Parse the JSON argument Codex passes to the notify program.
+    pub fn parse(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Payload `type`, defaulting to [`DEFAULT_TYPE`].
+    pub fn kind(&self) -> &str {
+        non_blank(&self.kind).unwrap_or(DEFAULT_TYPE)
+    }
+
+    /// Toast event: the `type` in snake case (`agent-turn-complete` →
+    /// `agent_turn_complete`).
+    pub fn event(&self) -> String {
+        self.kind().replace('-', "_")
+    }
+
+    /// Toast body: the assistant's last message, or when a turn ended
+    /// without one, the last user input it answered. Normalized like Claude
+    /// bodies ([`normalize_message`]).
+    pub fn message(&self, redactor: &Redactor) -> Option<String> {
+        non_blank(&self.last_assistant_message)
+            .or_else(|| {
+                self.input_messages
+                    .iter()
+                    .rev()
+                    .map(|m| m.trim())
+                    .find(|m| !m.is_empty())
+            })
+            .map(|m| normalize_message(m, redactor))
+            .filter(|m| !m.is_empty())
+    }
+
+    /// Codex can fire the notify command more than once per turn; the turn
+    /// id makes those collapse into a single toast.
+    pub fn dedupe_key(&self) -> Option<String> {
+        non_blank(&self.turn_id).map(|id| format!("codex-turn:{id}"))
+    }
+
+    /// Session context: the thread is the session, the payload `type` the
+    /// hook event. Not stamped.
+    pub fn context(&self) -> HookContext {
+        HookContext {
+            session_id: non_blank(&self.thread_id).map(str::to_string),
+            cwd: non_blank(&self.cwd).map(str::to_string),
+            hook_event_name: Some(self.kind().to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Notify request for this payload. `pid` is 0 and `process_tree` /
+    /// `hostname` are unset; the local and remote senders fill those in.
+    pub fn to_request(&self, redactor: &Redactor) -> NotifyRequest {
+        let context = self.context();
+        NotifyRequest {
+            pid: 0,
+            event: self.event(),
+            message: self.message(redactor),
+            title_hint: context.cwd_name(),
+            process_tree: None,
+            source: SOURCE.into(),
+            hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: self.dedupe_key(),
+            actions: vec![],
+            priority: Priority::Normal,
+            context: context.stamped(),
+            extra: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: &str = r#"{
+        "type": "agent-turn-complete",
+        "thread-id": "th-1",
+        "turn-id": "12345",
+        "cwd": "/home/me/proj",
+        "input-messages": ["Rename `foo` to `bar`"],
+        "last-assistant-message": "Rename complete and **verified**.",
+        "future-field": 1
+    }"#;
+
+    #[test]
+    fn full_payload_maps_to_request() {
+        let req = CodexNotification::parse(FULL)
+            .unwrap()
+            .to_request(&Redactor::default());
+        assert_eq!(req.event, "agent_turn_complete");
+        assert_eq!(
+            req.message.as_deref(),
+            Some("Rename complete and verified.")
+        );
+        assert_eq!(req.title_hint.as_deref(), Some("proj"));
+        assert_eq!(req.source, "codex");
+        assert_eq!(req.dedupe_key.as_deref(), Some("codex-turn:12345"));
+        assert_eq!(req.context.session_id.as_deref(), Some("th-1"));
+        assert_eq!(req.context.cwd.as_deref(), Some("/home/me/proj"));
+        assert_eq!(
+            req.context.hook_event_name.as_deref(),
+            Some("agent-turn-complete")
+        );
+        assert!(req.context.sent_at.is_some());
+    }
+
+    #[test]
+    fn empty_payload_uses_defaults() {
+        let n = CodexNotification::parse("{}").unwrap();
+        assert_eq!(n.event(), "agent_turn_complete");
+        assert_eq!(n.message(&Redactor::default()), None);
+        assert_eq!(n.dedupe_key(), None);
+        assert_eq!(n.to_request(&Redactor::default()).title_hint, None);
+    }
+
+    #[test]
+    fn falls_back_to_last_input_message() {
+        let n = CodexNotification::parse(
+            r#"{"input-messages": ["first", "second ", "  "], "last-assistant-message": " "}"#,
+        )
+        .unwrap();
+        assert_eq!(n.message(&Redactor::default()).as_deref(), Some("second"));
+    }
+
+    #[test]
+    fn message_is_redacted() {
+        let n = CodexNotification::parse(
+            r#"{"last-assistant-message": "Exported AWS_SECRET_ACCESS_KEY=wJalrXUtnFEMI/K7MDENG"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            n.message(&Redactor::default()).as_deref(),
+            Some("Exported AWS_SECRET_ACCESS_KEY=[REDACTED]")
+        );
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        assert!(CodexNotification::parse("not json").is_err());
+        assert!(CodexNotification::parse(r#"{"input-messages": "x"}"#).is_err());
+    }
+}
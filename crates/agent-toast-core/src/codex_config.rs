@@ -0,0 +1,283 @@
+//! Registering agent-toast as the Codex CLI `notify` program in
+//! `~/.codex/config.toml`.
+//!
+//! Edits go through `toml_edit`, so comments, key order and formatting of
+//! the rest of the file survive, and a file that doesn't parse is reported
+//! instead of being replaced. A `notify` program the user already had is not
+//! overwritten: it is kept as a chain (`--chain <arg>` per argument) and
+//! agent-toast [`forward`]s each payload to it, and uninstalling puts it back.
+
+use std::process::{Child, Command, Stdio};
+use toml_edit::{value, Array, DocumentMut};
+
+/// Flag that switches agent-toast into Codex mode.
+pub const CODEX_FLAG: &str = "--codex";
+
+/// Flag carrying one argument of the chained notify program.
+pub const CHAIN_FLAG: &str = "--chain";
+
+/// The `notify` command line in `content`, or `None` when it isn't set.
+pub fn notify(content: &str) -> Result<Option<Vec<String>>, String> {
+    notify_of(&parse(content)?)
+}
+
+/// `content` with `notify` set to `program` (e.g. `[<exe>, "--codex"]`).
+///
+/// A foreign `notify` program is chained after `program`; re-installing over
+/// agent-toast's own entry (say, after the exe moved) keeps its chain.
+pub fn install(content: &str, program: &[String]) -> Result<String, String> {
+    let mut doc = parse(content)?;
+    let chain = match notify_of(&doc)? {
+        Some(argv) if is_ours(&argv) => chained(&argv),
+        Some(argv) => argv,
+        None => Vec::new(),
+    };
+    let mut argv = program.to_vec();
+    for arg in chain {
+        argv.extend([CHAIN_FLAG.to_string(), arg]);
+    }
+    set_notify(&mut doc, &argv);
+    Ok(doc.to_string())
+}
+
+/// `content` without agent-toast's `notify` entry: the chained program is
+/// restored, or `notify` removed when there was none. A `notify` that isn't
+/// agent-toast's is left alone. `None` when nothing changes.
+pub fn uninstall(content: &str) -> Result<Option<String>, String> {
+    let mut doc = parse(content)?;
+    match notify_of(&doc)? {
+        Some(argv) if is_ours(&argv) => {
+            let chain = chained(&argv);
+            if chain.is_empty() {
+                doc.remove("notify");
+            } else {
+                set_notify(&mut doc, &chain);
+            }
+            Ok(Some(doc.to_string()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Returns true if `argv` is agent-toast's Codex notify command: an
+/// agent-toast binary run with [`CODEX_FLAG`].
+pub fn is_ours(argv: &[String]) -> bool {
+    argv.first()
+        .is_some_and(|p| crate::hook_config::is_agent_toast_program(p))
+        && argv.iter().any(|a| a == CODEX_FLAG)
+}
+
+/// The chained program in agent-toast's notify `argv`: every
+/// [`CHAIN_FLAG`] argument, in order. A value is never read as a flag, so
+/// the chained program may itself contain `--chain`.
+pub fn chained(argv: &[String]) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut args = argv.iter();
+    while let Some(arg) = args.next() {
+        if arg == CHAIN_FLAG {
+            chain.extend(args.next().cloned());
+        }
+    }
+    chain
+}
+
+/// Run the chained notify program with `payload` as its last argument, as
+/// Codex would have, without waiting for it. `None` for an empty chain.
+pub fn forward(chain: &[String], payload: &str) -> std::io::Result<Option<Child>> {
+    let Some((program, args)) = chain.split_first() else {
+        return Ok(None);
+    };
+    Command::new(program)
+        .args(args)
+        .arg(payload)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(Some)
+}
+
+fn parse(content: &str) -> Result<DocumentMut, String> {
+    content
+        .parse()
+        .map_err(|e| format!("config.toml is not valid TOML: {e}"))
+}
+
+fn notify_of(doc: &DocumentMut) -> Result<Option<Vec<String>>, String> {
+    let Some(item) = doc.get("notify") else {
+        return Ok(None);
+    };
+    item.as_array()
+        .and_then(|a| {
+            a.iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .map(Some)
+        .ok_or_else(|| "`notify` in config.toml is not a list of strings".to_string())
+}
+
+/// Set `notify`, keeping the old value's position and trailing comment.
+fn set_notify(doc: &mut DocumentMut, argv: &[String]) {
+    let array: Array = argv.iter().map(String::as_str).collect();
+    match doc.get_mut("notify").and_then(|item| item.as_value_mut()) {
+        Some(old) => {
+            let decor = old.decor().clone();
+            *old = array.into();
+            *old.decor_mut() = decor;
+        }
+        None => doc["notify"] = value(array),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ours(exe: &str) -> Vec<String> {
+        vec![exe.into(), CODEX_FLAG.into()]
+    }
+
+    #[test]
+    fn install_into_empty_and_commented_files() {
+        let out = install("", &ours("/opt/agent-toast")).unwrap();
+        assert_eq!(out, "notify = [\"/opt/agent-toast\", \"--codex\"]\n");
+
+        let original =
+            "# my codex config\nmodel = \"o3\" # fast\n\n[profiles.work]\nmodel = \"gpt-5\"\n";
+        let out = install(original, &ours("/opt/agent-toast")).unwrap();
+        assert!(
+            out.starts_with("# my codex config\nmodel = \"o3\" # fast\n"),
+            "{out}"
+        );
+        assert!(
+            out.contains("\n[profiles.work]\nmodel = \"gpt-5\"\n"),
+            "{out}"
+        );
+        assert_eq!(notify(&out).unwrap(), Some(ours("/opt/agent-toast")));
+        // notify stays a root key, ahead of the tables.
+        assert!(out.find("notify").unwrap() < out.find("[profiles.work]").unwrap());
+    }
+
+    #[test]
+    fn install_chains_foreign_notify_and_uninstall_restores_it() {
+        let original =
+            "# notify me\nnotify = [\"python3\", \"/home/me/notify.py\"] # keep\nmodel = \"o3\"\n";
+        let installed = install(original, &ours("/opt/agent-toast")).unwrap();
+        let argv = notify(&installed).unwrap().unwrap();
+        assert_eq!(
+            argv,
+            [
+                "/opt/agent-toast",
+                "--codex",
+                "--chain",
+                "python3",
+                "--chain",
+                "/home/me/notify.py"
+            ]
+        );
+        assert_eq!(chained(&argv), ["python3", "/home/me/notify.py"]);
+        assert!(
+            installed.starts_with("# notify me\nnotify = ["),
+            "{installed}"
+        );
+        assert!(
+            installed.contains("] # keep\nmodel = \"o3\"\n"),
+            "{installed}"
+        );
+
+        // Re-installing (e.g. a moved exe) keeps the chain, not a chain of us.
+        let moved = install(&installed, &ours("/usr/bin/agent-toast")).unwrap();
+        assert_eq!(
+            chained(&notify(&moved).unwrap().unwrap()),
+            ["python3", "/home/me/notify.py"]
+        );
+
+        assert_eq!(uninstall(&moved).unwrap().unwrap(), original);
+    }
+
+    #[test]
+    fn chained_program_may_contain_chain_flag() {
+        let original = "notify = [\"prog\", \"--chain\", \"x\"]\n";
+        let installed = install(original, &ours("/opt/agent-toast")).unwrap();
+        assert_eq!(
+            chained(&notify(&installed).unwrap().unwrap()),
+            ["prog", "--chain", "x"]
+        );
+        let moved = install(&installed, &ours("/usr/bin/agent-toast")).unwrap();
+        assert_eq!(
+            chained(&notify(&moved).unwrap().unwrap()),
+            ["prog", "--chain", "x"]
+        );
+        assert_eq!(uninstall(&moved).unwrap().unwrap(), original);
+    }
+
+    #[test]
+    fn uninstall_removes_only_our_notify() {
+        let installed = install("model = \"o3\"\n", &ours("C:\\at\\agent-toast.exe")).unwrap();
+        assert_eq!(uninstall(&installed).unwrap().unwrap(), "model = \"o3\"\n");
+
+        let foreign = "notify = [\"notify-send\", \"codex\"]\n";
+        assert_eq!(uninstall(foreign).unwrap(), None);
+        assert_eq!(uninstall("").unwrap(), None);
+    }
+
+    #[test]
+    fn invalid_config_is_an_error_not_a_reset() {
+        let broken = "model = \"o3\"\n[profiles\n";
+        assert!(install(broken, &ours("/opt/agent-toast"))
+            .unwrap_err()
+            .contains("not valid TOML"));
+        assert!(uninstall(broken).is_err());
+        assert!(
+            install("notify = \"agent-toast\"\n", &ours("/opt/agent-toast"))
+                .unwrap_err()
+                .contains("not a list of strings")
+        );
+    }
+
+    #[test]
+    fn is_ours_needs_agent_toast_binary_and_codex_flag() {
+        let argv = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_ours(&argv(&[
+            "agent-toast-send",
+            "--url",
+            "http://x",
+            "--codex"
+        ])));
+        assert!(is_ours(&argv(&[
+            "C:\\Agent Toast\\Agent Toast.exe",
+            "--codex"
+        ])));
+        assert!(!is_ours(&argv(&["agent-toast"])));
+        assert!(!is_ours(&argv(&[
+            "/home/me/agent-toast-wrapper.sh",
+            "--codex"
+        ])));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn forward_appends_payload() {
+        let out = std::env::temp_dir().join(format!(
+            "agent-toast-codex-forward-{}.json",
+            std::process::id()
+        ));
+        let chain = [
+            "sh".to_string(),
+            "-c".into(),
+            format!("printf %s \"$1\" > '{}'", out.display()),
+            "sh".into(),
+        ];
+        let mut child = forward(&chain, "{\"type\":\"agent-turn-complete\"}")
+            .unwrap()
+            .unwrap();
+        assert!(child.wait().unwrap().success());
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "{\"type\":\"agent-turn-complete\"}"
+        );
+        let _ = std::fs::remove_file(&out);
+        assert!(forward(&[], "{}").unwrap().is_none());
+    }
+}
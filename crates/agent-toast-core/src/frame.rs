@@ -0,0 +1,254 @@
+//! Length-prefixed framing for byte-stream transports (the Windows named pipe
+//! today).
+//!
+//! A frame is a 4-byte little-endian payload length followed by the payload,
+//! which is JSON by convention. A client writes one request frame and may read
+//! one response frame on the same connection; a peer that hangs up instead of
+//! answering reads as "no response" rather than an error, so fire-and-forget
+//! clients and servers that predate response frames keep working.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+
+/// Size of the length prefix.
+pub const HEADER_LEN: usize = 4;
+
+/// Largest payload either side accepts. Far above any real notification, but
+/// keeps a corrupt or hostile length prefix from allocating gigabytes.
+pub const MAX_FRAME_BYTES: usize = 1024 * 1024;
+
+fn check_len(len: usize) -> io::Result<usize> {
+    if len > MAX_FRAME_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds the {MAX_FRAME_BYTES} byte limit"),
+        ));
+    }
+    Ok(len)
+}
+
+/// Header + `payload` as one buffer, for transports that send whole messages.
+pub fn encode(payload: &[u8]) -> io::Result<Vec<u8>> {
+    let len = check_len(payload.len())?;
+    let mut frame = Vec::with_capacity(HEADER_LEN + len);
+    frame.extend_from_slice(&(len as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Write one frame and flush.
+pub fn write_frame(w: &mut impl Write, payload: &[u8]) -> io::Result<()> {
+    w.write_all(&encode(payload)?)?;
+    w.flush()
+}
+
+/// Serialize `value` as JSON and write it as one frame.
+pub fn write_json<T: Serialize>(w: &mut impl Write, value: &T) -> io::Result<()> {
+    write_frame(w, &serde_json::to_vec(value)?)
+}
+
+/// Read one frame, blocking until it is complete. Short reads are retried.
+///
+/// `Ok(None)` when the peer closed the stream (EOF or broken pipe) before
+/// sending any byte of a header; a stream that ends mid-frame is an
+/// [`io::ErrorKind::UnexpectedEof`] error.
+pub fn read_frame(r: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut header = [0u8; HEADER_LEN];
+    let mut filled = 0;
+    while filled < HEADER_LEN {
+        match r.read(&mut header[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "stream ended inside a frame header",
+                ))
+            }
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) if filled == 0 && e.kind() == io::ErrorKind::BrokenPipe => return Ok(None),
+            Err(e) => return Err(e),
+        }
+    }
+    let mut payload = vec![0u8; check_len(u32::from_le_bytes(header) as usize)?];
+    r.read_exact(&mut payload)?;
+    Ok(Some(payload))
+}
+
+/// [`read_frame`] and decode the payload as JSON.
+pub fn read_json<T: DeserializeOwned>(r: &mut impl Read) -> io::Result<Option<T>> {
+    match read_frame(r)? {
+        Some(payload) => Ok(Some(serde_json::from_slice(&payload)?)),
+        None => Ok(None),
+    }
+}
+
+/// Incremental decoder for transports that hand over bytes in arbitrary
+/// chunks (non-blocking sockets, replayed captures): [`push`](Self::push)
+/// whatever arrived, then drain complete frames with
+/// [`next_frame`](Self::next_frame).
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// The next complete frame's payload, or `None` until more bytes arrive.
+    /// An oversized length prefix is an error; the stream can't be resynced
+    /// after that, so the caller should drop the connection.
+    pub fn next_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.buf.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        let header: [u8; HEADER_LEN] = self.buf[..HEADER_LEN].try_into().unwrap();
+        let len = check_len(u32::from_le_bytes(header) as usize)?;
+        if self.buf.len() < HEADER_LEN + len {
+            return Ok(None);
+        }
+        let payload = self.buf[HEADER_LEN..HEADER_LEN + len].to_vec();
+        self.buf.drain(..HEADER_LEN + len);
+        Ok(Some(payload))
+    }
+
+    /// Bytes received but not yet returned as a frame.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotifyRequest;
+    use std::io::Cursor;
+
+    /// Reader that returns at most one byte per call.
+    struct Trickle<R>(R);
+
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(1);
+            self.0.read(&mut buf[..n])
+        }
+    }
+
+    fn sample() -> NotifyRequest {
+        serde_json::from_str(r#"{"pid":7,"event":"task_complete","message":"안녕 ✓"}"#).unwrap()
+    }
+
+    #[test]
+    fn encode_prefixes_little_endian_length() {
+        let frame = encode(b"abc").unwrap();
+        assert_eq!(frame, [3, 0, 0, 0, b'a', b'b', b'c']);
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let mut buf = Vec::new();
+        write_json(&mut buf, &sample()).unwrap();
+        let decoded: NotifyRequest = read_json(&mut Cursor::new(buf)).unwrap().unwrap();
+        assert_eq!(decoded.pid, 7);
+        assert_eq!(decoded.message.as_deref(), Some("안녕 ✓"));
+    }
+
+    #[test]
+    fn read_frame_survives_short_reads() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, b"first").unwrap();
+        write_frame(&mut buf, b"").unwrap();
+        let mut r = Trickle(Cursor::new(buf));
+        assert_eq!(read_frame(&mut r).unwrap().unwrap(), b"first");
+        assert_eq!(read_frame(&mut r).unwrap().unwrap(), b"");
+        assert!(read_frame(&mut r).unwrap().is_none());
+    }
+
+    #[test]
+    fn hang_up_before_reply_is_no_reply() {
+        assert!(read_frame(&mut Cursor::new(Vec::new())).unwrap().is_none());
+
+        struct Broken;
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        assert!(read_frame(&mut Broken).unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated_frame_is_an_error() {
+        let err = read_frame(&mut Cursor::new(vec![5, 0])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err = read_frame(&mut Cursor::new(vec![5, 0, 0, 0, b'a'])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected_both_ways() {
+        let big = vec![b'x'; MAX_FRAME_BYTES + 1];
+        assert_eq!(
+            write_frame(&mut Vec::new(), &big).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // Only the header is sent: the limit is checked before allocating.
+        let header = ((MAX_FRAME_BYTES + 1) as u32).to_le_bytes();
+        assert_eq!(
+            read_frame(&mut Cursor::new(header)).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        let mut dec = FrameDecoder::new();
+        dec.push(&header);
+        assert!(dec.next_frame().is_err());
+    }
+
+    #[test]
+    fn max_size_frame_is_accepted() {
+        let payload = vec![b'x'; MAX_FRAME_BYTES];
+        let frame = encode(&payload).unwrap();
+        assert_eq!(
+            read_frame(&mut Cursor::new(frame)).unwrap().unwrap().len(),
+            MAX_FRAME_BYTES
+        );
+    }
+
+    #[test]
+    fn decoder_reassembles_split_frames() {
+        let mut stream = encode(b"one").unwrap();
+        stream.extend(encode(b"two").unwrap());
+
+        let mut dec = FrameDecoder::new();
+        let mut frames = Vec::new();
+        for chunk in stream.chunks(3) {
+            dec.push(chunk);
+            while let Some(frame) = dec.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames, [b"one".to_vec(), b"two".to_vec()]);
+        assert_eq!(dec.buffered(), 0);
+    }
+
+    #[test]
+    fn decoder_waits_for_full_header() {
+        let mut dec = FrameDecoder::new();
+        dec.push(&[3, 0]);
+        assert!(dec.next_frame().unwrap().is_none());
+        dec.push(&[0, 0, b'a', b'b']);
+        assert!(dec.next_frame().unwrap().is_none());
+        assert_eq!(dec.buffered(), 6);
+        dec.push(b"c");
+        assert_eq!(dec.next_frame().unwrap().unwrap(), b"abc");
+    }
+}
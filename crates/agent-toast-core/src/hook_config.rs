@@ -1,10 +1,13 @@
-//! Pure JSON merge logic for Claude Code's `~/.claude/settings.json`.
+//! Pure JSON merge and diff logic for Claude Code's `settings.json` files.
 //!
 //! Both the desktop GUI (`setup.rs`) and the remote CLI (`agent-toast-send init`)
 //! use this to add/remove agent-toast hook entries while preserving any unrelated
-//! hooks the user has configured.
+//! hooks the user has configured. Hooks can live in any [`Scope`]; Claude Code
+//! runs them all, so [`duplicate_hooks`] flags ones registered twice.
 
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 
 /// A hook entry to register into `settings.json`.
 ///
@@ -15,20 +18,145 @@ use serde_json::{json, Value};
 /// `matcher` applies only to events that support matchers (e.g. `Notification`
 /// uses `"permission_prompt"` / `"elicitation_prompt"` / `"idle"`). Pass `None`
 /// for events that don't take a matcher.
+///
+/// `timeout` and `run_async` become the hook's `timeout` and `async` options.
+#[derive(Debug, Clone, Default)]
 pub struct HookEntry {
     pub event_key: &'static str,
     pub matcher: Option<&'static str>,
     pub command: String,
+    /// Seconds Claude Code lets the command run before killing it; `None`
+    /// keeps Claude's default (60 s).
+    pub timeout: Option<u32>,
+    /// Run the command in the background (`"async": true`) so Claude Code
+    /// doesn't wait for it. Only for commands whose output Claude ignores,
+    /// never for `decide`.
+    pub run_async: bool,
+}
+
+/// Hook `timeout` agent-toast registers its commands with. Delivering a toast
+/// takes well under a second, so an unreachable desktop cuts the hook short
+/// instead of stalling Claude Code for its default timeout.
+pub const DEFAULT_TIMEOUT_SECS: u32 = 10;
+
+/// Hook `timeout` for a command that waits up to `wait_secs` for a click
+/// (`decide`, actions): the wait plus the `timeout` it gets to deliver.
+pub fn waiting_timeout(wait_secs: u64, timeout: u32) -> u32 {
+    u32::try_from(wait_secs)
+        .unwrap_or(u32::MAX)
+        .saturating_add(timeout)
 }
 
-/// Returns true if the command string belongs to agent-toast.
+/// Toast event for each Claude Code hook: `(hook_event_name, matcher, event)`.
 ///
-/// Matches the Windows local binary (`agent-toast.exe`), the Windows installer
-/// product name (`Agent Toast`), the remote CLI (`agent-toast-send`), and the
-/// underscore variant (`agent_toast`), all case-insensitively.
+/// `matcher` is compared with the payload's `notification_type` (what the
+/// `Notification` matcher selects on); `None` matches anything. The first
+/// matching row wins, so specific rows come before the catch-all.
+pub const EVENT_MAP: &[(&str, Option<&str>, &str)] = &[
+    ("SessionStart", None, "session_start"),
+    ("Stop", None, "task_complete"),
+    (
+        "Notification",
+        Some("permission_prompt"),
+        "user_input_required",
+    ),
+    (
+        "Notification",
+        Some("elicitation_dialog"),
+        "user_input_required",
+    ),
+    ("Notification", Some("idle_prompt"), "user_input_required"),
+    ("Notification", None, "user_input_required"),
+    ("PermissionRequest", None, "user_input_required"),
+    ("PostToolUseFailure", None, "error"),
+    ("SessionEnd", None, "task_complete"),
+    ("SubagentStart", None, "task_complete"),
+    ("SubagentStop", None, "task_complete"),
+    ("PreCompact", None, "task_complete"),
+    ("Setup", None, "task_complete"),
+    ("UserPromptSubmit", None, "task_complete"),
+    ("PreToolUse", None, "task_complete"),
+    ("PostToolUse", None, "task_complete"),
+];
+
+/// The toast event for a hook, per [`EVENT_MAP`]. `None` for hook events the
+/// table doesn't know.
+pub fn event_for(hook_event_name: &str, matcher: Option<&str>) -> Option<&'static str> {
+    EVENT_MAP
+        .iter()
+        .find(|(name, m, _)| *name == hook_event_name && m.is_none_or(|m| Some(m) == matcher))
+        .map(|(_, _, event)| *event)
+}
+
+/// The toast event for a hook's stdin JSON, from its `hook_event_name` and
+/// `notification_type`. Lets a hook command omit `--event`.
+pub fn event_from_hook(hook: &Value) -> Option<&'static str> {
+    let name = hook.get("hook_event_name")?.as_str()?.trim();
+    let matcher = hook.get("notification_type").and_then(|t| t.as_str());
+    event_for(name, matcher)
+}
+
+/// Flag appended to every hook command agent-toast writes. Both binaries
+/// accept and ignore it; it only marks the entry as ours. Being part of the
+/// command string, it survives however Claude Code rewrites settings.json.
+pub const MARKER: &str = "--agent-toast-managed";
+
+/// Returns true if the command carries [`MARKER`].
+pub fn is_marked(command: &str) -> bool {
+    command.split_whitespace().any(|arg| arg == MARKER)
+}
+
+/// `command` with [`MARKER`] appended, unless it already has it.
+pub fn mark(command: &str) -> String {
+    if is_marked(command) {
+        command.to_string()
+    } else {
+        format!("{command} {MARKER}")
+    }
+}
+
+/// Returns true for an unmarked command written before [`MARKER`] existed:
+/// its program is an agent-toast binary — the desktop app (`agent-toast.exe`,
+/// installer product name `Agent Toast.exe`) or the remote CLI
+/// (`agent-toast-send`), case-insensitively. A wrapper script that merely has
+/// "agent-toast" in its path or name is not ours.
+pub fn is_legacy_cmd(command: &str) -> bool {
+    !is_marked(command) && is_agent_toast_program(program(command))
+}
+
+/// Returns true if `program` (a path) is an agent-toast binary: the desktop
+/// app or `agent-toast-send`, case-insensitively, with or without `.exe`.
+pub fn is_agent_toast_program(program: &str) -> bool {
+    let name = program
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    let name = name.replace(['_', ' '], "-");
+    name == "agent-toast" || name == "agent-toast-send"
+}
+
+/// Returns true if the command belongs to agent-toast: marked, or a legacy
+/// command that [`migrate_legacy_hooks`] would mark.
 pub fn is_agent_toast_cmd(command: &str) -> bool {
-    let lc = command.to_lowercase();
-    lc.contains("agent-toast") || lc.contains("agent_toast") || lc.contains("agent toast")
+    is_marked(command) || is_legacy_cmd(command)
+}
+
+/// Append [`MARKER`] to every legacy agent-toast command in `root.hooks.*`
+/// (see [`is_legacy_cmd`]). Returns how many were migrated.
+pub fn migrate_legacy_hooks(root: &mut Value) -> usize {
+    let mut migrated = 0;
+    for inner in inner_hooks_mut(root) {
+        let Some(command) = inner.get("command").and_then(|c| c.as_str()) else {
+            continue;
+        };
+        if is_legacy_cmd(command) {
+            inner["command"] = Value::String(mark(command));
+            migrated += 1;
+        }
+    }
+    migrated
 }
 
 /// Merge `entries` into `root`, replacing any existing agent-toast hooks and
@@ -36,7 +164,8 @@ pub fn is_agent_toast_cmd(command: &str) -> bool {
 ///
 /// Semantics: "replace all agent-toast hooks with this set". Any agent-toast
 /// entries on event_keys not present in `entries` are removed, so the result
-/// exactly matches `entries` with all non-agent-toast hooks preserved.
+/// exactly matches `entries` with all non-agent-toast hooks preserved. The
+/// new commands are written with [`MARKER`].
 ///
 /// `root` is the parsed `settings.json` as a `serde_json::Value`. The returned
 /// `Value` is ready to be serialized back out.
@@ -79,46 +208,366 @@ pub fn merge_agent_toast_hooks(mut root: Value, entries: &[HookEntry]) -> Value
 }
 
 /// Remove all agent-toast entries from `root.hooks.*`, preserving other hooks.
+///
+/// Legacy commands are migrated first; after that only marked commands are
+/// removed. A matcher group left with no commands is dropped with them.
 pub fn remove_agent_toast_hooks(mut root: Value) -> Value {
+    migrate_legacy_hooks(&mut root);
     let Some(hooks) = root.get_mut("hooks").and_then(|v| v.as_object_mut()) else {
         return root;
     };
     for (_event_key, arr_val) in hooks.iter_mut() {
         if let Some(arr) = arr_val.as_array_mut() {
-            arr.retain(|outer| !outer_contains_agent_toast_cmd(outer));
+            arr.retain_mut(|outer| {
+                let Some(inner) = outer.get_mut("hooks").and_then(|h| h.as_array_mut()) else {
+                    return true;
+                };
+                let before = inner.len();
+                inner.retain(|cmd_entry| {
+                    !cmd_entry
+                        .get("command")
+                        .and_then(|c| c.as_str())
+                        .is_some_and(is_marked)
+                });
+                inner.len() == before || !inner.is_empty()
+            });
         }
     }
     root
 }
 
+/// How one hook command differs between two `settings.json` versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HookChange {
+    Added {
+        command: String,
+    },
+    Removed {
+        command: String,
+    },
+    /// Same event and matcher, different command or options.
+    Changed {
+        before: String,
+        after: String,
+    },
+}
+
+/// A [`HookChange`] on one event (and matcher).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookDiffEntry {
+    pub event_key: String,
+    pub matcher: Option<String>,
+    #[serde(flatten)]
+    pub change: HookChange,
+}
+
+/// Every hook command added, removed or changed by a rewrite, grouped by
+/// event in name order. Built by [`diff_hooks`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HookDiff {
+    pub entries: Vec<HookDiffEntry>,
+}
+
+impl HookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// One line per change under an `Event [matcher]:` heading: `+` added, `-`
+/// removed, `~` changed (old command, then `→` new command).
+impl std::fmt::Display for HookDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut heading = None;
+        for e in &self.entries {
+            let this = (&e.event_key, &e.matcher);
+            if heading != Some(this) {
+                match &e.matcher {
+                    Some(m) => writeln!(f, "{} [{m}]:", e.event_key)?,
+                    None => writeln!(f, "{}:", e.event_key)?,
+                }
+                heading = Some(this);
+            }
+            match &e.change {
+                HookChange::Added { command } => writeln!(f, "  + {command}")?,
+                HookChange::Removed { command } => writeln!(f, "  - {command}")?,
+                HookChange::Changed { before, after } => {
+                    writeln!(f, "  ~ {before}")?;
+                    writeln!(f, "    → {after}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare the hook commands of two parsed `settings.json` roots.
+///
+/// Commands are matched per event and matcher; identical ones are left out.
+/// A command's `timeout` / `async` options are shown after it, e.g.
+/// `agent-toast-send … (timeout 10s, async)`.
+/// A removed and an added command under the same event and matcher are
+/// reported together as [`HookChange::Changed`]. Pure: nothing is written, so
+/// callers can preview `merge_agent_toast_hooks` / `remove_agent_toast_hooks`
+/// before saving.
+pub fn diff_hooks(before: &Value, after: &Value) -> HookDiff {
+    use std::collections::BTreeSet;
+
+    let events: BTreeSet<&String> = [before, after]
+        .iter()
+        .filter_map(|root| root.get("hooks").and_then(|h| h.as_object()))
+        .flat_map(|hooks| hooks.keys())
+        .collect();
+
+    let mut entries = Vec::new();
+    for event_key in events {
+        let mut removed = hook_commands(before, event_key);
+        let mut added = hook_commands(after, event_key);
+        // Drop commands present on both sides, one for one.
+        removed.retain(|item| match added.iter().position(|a| a == item) {
+            Some(i) => {
+                added.remove(i);
+                false
+            }
+            None => true,
+        });
+
+        let mut push = |matcher: Option<String>, change| {
+            entries.push(HookDiffEntry {
+                event_key: event_key.clone(),
+                matcher,
+                change,
+            })
+        };
+        for (matcher, command) in removed {
+            match added.iter().position(|(m, _)| *m == matcher) {
+                Some(i) => {
+                    let (_, after) = added.remove(i);
+                    push(
+                        matcher,
+                        HookChange::Changed {
+                            before: command,
+                            after,
+                        },
+                    );
+                }
+                None => push(matcher, HookChange::Removed { command }),
+            }
+        }
+        for (matcher, command) in added {
+            push(matcher, HookChange::Added { command });
+        }
+    }
+    HookDiff { entries }
+}
+
+/// Which `settings.json` a hook is registered in. Claude Code merges the
+/// hooks of every scope, so the same hook in two scopes runs twice.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// `~/.claude/settings.json`, for every project.
+    #[default]
+    User,
+    /// `<project>/.claude/settings.json`, shared through the repository.
+    Project,
+    /// `<project>/.claude/settings.local.json`, this checkout only.
+    Local,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::User, Scope::Project, Scope::Local];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::User => "user",
+            Scope::Project => "project",
+            Scope::Local => "local",
+        }
+    }
+
+    /// The settings file of this scope, for the user's `home` and the
+    /// `project` root (ignored for [`Scope::User`]).
+    pub fn settings_path(self, home: &Path, project: &Path) -> PathBuf {
+        match self {
+            Scope::User => home.join(".claude").join("settings.json"),
+            Scope::Project => project.join(".claude").join("settings.json"),
+            Scope::Local => project.join(".claude").join("settings.local.json"),
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("unknown scope `{s}` (expected user, project or local)"))
+    }
+}
+
+/// An agent-toast hook (event and matcher) registered in several scopes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateHook {
+    pub event_key: String,
+    pub matcher: Option<String>,
+    pub scopes: Vec<Scope>,
+}
+
+impl std::fmt::Display for DuplicateHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.matcher {
+            Some(m) => write!(f, "{} [{m}]", self.event_key)?,
+            None => write!(f, "{}", self.event_key)?,
+        }
+        let scopes: Vec<&str> = self.scopes.iter().map(|s| s.as_str()).collect();
+        write!(
+            f,
+            " is registered in {} settings; each will show a toast",
+            scopes.join(" and ")
+        )
+    }
+}
+
+/// Agent-toast hooks that appear, by event and matcher, in more than one of
+/// `roots` (parsed `settings.json` per scope). Sorted by event name.
+pub fn duplicate_hooks(roots: &[(Scope, &Value)]) -> Vec<DuplicateHook> {
+    use std::collections::BTreeMap;
+
+    let mut seen: BTreeMap<(String, Option<String>), Vec<Scope>> = BTreeMap::new();
+    for (scope, root) in roots {
+        let Some(hooks) = root.get("hooks").and_then(|h| h.as_object()) else {
+            continue;
+        };
+        for event_key in hooks.keys() {
+            for (matcher, command) in hook_commands(root, event_key) {
+                if !is_agent_toast_cmd(&command) {
+                    continue;
+                }
+                let scopes = seen.entry((event_key.clone(), matcher)).or_default();
+                if !scopes.contains(scope) {
+                    scopes.push(*scope);
+                }
+            }
+        }
+    }
+    seen.into_iter()
+        .filter(|(_, scopes)| scopes.len() > 1)
+        .map(|((event_key, matcher), mut scopes)| {
+            scopes.sort();
+            DuplicateHook {
+                event_key,
+                matcher,
+                scopes,
+            }
+        })
+        .collect()
+}
+
 // ────────── internal helpers ──────────
 
 /// Claude Code settings.json nests hooks as:
 ///   "Stop": [ { "hooks": [ {"type":"command","command":"..."} ] } ]
 /// or with a matcher:
 ///   "Notification": [ { "matcher": "permission_prompt", "hooks": [ {...} ] } ]
-/// This helper checks if any of the inner command entries are agent-toast's.
-fn outer_contains_agent_toast_cmd(outer: &Value) -> bool {
-    outer
+/// This yields every inner `{"type":"command",...}` object.
+fn inner_hooks_mut(root: &mut Value) -> impl Iterator<Item = &mut Value> {
+    root.get_mut("hooks")
+        .and_then(|h| h.as_object_mut())
+        .into_iter()
+        .flat_map(|hooks| hooks.values_mut())
+        .filter_map(|outers| outers.as_array_mut())
+        .flatten()
+        .filter_map(|outer| outer.get_mut("hooks").and_then(|h| h.as_array_mut()))
+        .flatten()
+}
+
+/// The program part of a hook command: the quoted leading path, or the text
+/// before the first ` --` when it names an `.exe` (unquoted Windows paths may
+/// contain spaces), or else the first word.
+fn program(command: &str) -> &str {
+    let command = command.trim_start();
+    for quote in ['"', '\''] {
+        if let Some(rest) = command.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or_default();
+        }
+    }
+    let head = command.split(" --").next().unwrap_or_default().trim_end();
+    if head.to_lowercase().ends_with(".exe") {
+        return head;
+    }
+    command.split_whitespace().next().unwrap_or_default()
+}
+
+/// Every `(matcher, command)` registered on `root.hooks.<event_key>`, with
+/// the command's options appended (see [`describe`]).
+fn hook_commands(root: &Value, event_key: &str) -> Vec<(Option<String>, String)> {
+    let Some(outers) = root
         .get("hooks")
-        .and_then(|h| h.as_array())
-        .map(|arr| {
-            arr.iter().any(|cmd_entry| {
-                cmd_entry
-                    .get("command")
-                    .and_then(|c| c.as_str())
-                    .is_some_and(is_agent_toast_cmd)
-            })
+        .and_then(|h| h.get(event_key))
+        .and_then(|v| v.as_array())
+    else {
+        return Vec::new();
+    };
+    outers
+        .iter()
+        .flat_map(|outer| {
+            let matcher = outer
+                .get("matcher")
+                .and_then(|m| m.as_str())
+                .map(str::to_string);
+            outer
+                .get("hooks")
+                .and_then(|h| h.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(describe)
+                .map(move |command| (matcher.clone(), command))
         })
-        .unwrap_or(false)
+        .collect()
+}
+
+/// An inner hook's command followed by its `timeout` / `async` options, if
+/// any: `cmd (timeout 10s, async)`.
+fn describe(inner: &Value) -> Option<String> {
+    let command = inner.get("command")?.as_str()?;
+    let mut options = Vec::new();
+    if let Some(timeout) = inner.get("timeout").and_then(|t| t.as_u64()) {
+        options.push(format!("timeout {timeout}s"));
+    }
+    if inner.get("async").and_then(|a| a.as_bool()) == Some(true) {
+        options.push("async".to_string());
+    }
+    Some(if options.is_empty() {
+        command.to_string()
+    } else {
+        format!("{command} ({})", options.join(", "))
+    })
 }
 
 /// Builds the outer entry Claude Code expects, with or without a matcher.
 fn build_outer_entry(e: &HookEntry) -> Value {
-    let inner = json!({
+    let mut inner = json!({
         "type": "command",
-        "command": e.command,
+        "command": mark(&e.command),
     });
+    if let Some(timeout) = e.timeout {
+        inner["timeout"] = json!(timeout);
+    }
+    if e.run_async {
+        inner["async"] = json!(true);
+    }
     match e.matcher {
         Some(m) => json!({
             "matcher": m,
@@ -143,11 +592,69 @@ mod tests {
             r#""C:\tools\agent-toast.exe" --pid 1 --event task_complete"#
         ));
         assert!(is_agent_toast_cmd("AGENT-TOAST --event x"));
-        assert!(is_agent_toast_cmd("my_agent_toast_wrapper"));
+        assert!(is_agent_toast_cmd("agent-toast-send decide --url http://x"));
+        assert!(is_agent_toast_cmd("/opt/run.sh --agent-toast-managed"));
         assert!(!is_agent_toast_cmd("/usr/bin/other-tool --flag"));
         assert!(!is_agent_toast_cmd(""));
     }
 
+    #[test]
+    fn wrapper_scripts_named_after_agent_toast_are_not_ours() {
+        assert!(!is_agent_toast_cmd("my_agent_toast_wrapper"));
+        assert!(!is_agent_toast_cmd(
+            "/home/me/agent-toast-scripts/notify.sh --event stop"
+        ));
+        assert!(!is_agent_toast_cmd("run-agent-toast.sh"));
+        assert!(!is_agent_toast_cmd("echo agent-toast-send"));
+    }
+
+    #[test]
+    fn mark_is_idempotent() {
+        let marked = mark("agent-toast-send --event x");
+        assert_eq!(marked, "agent-toast-send --event x --agent-toast-managed");
+        assert_eq!(mark(&marked), marked);
+        assert!(is_marked(&marked));
+        assert!(!is_legacy_cmd(&marked));
+        assert!(!is_marked("agent-toast-send --agent-toast-managed-ish"));
+    }
+
+    #[test]
+    fn migrate_marks_only_legacy_commands() {
+        let mut root = json!({ "hooks": { "Stop": [
+            { "hooks": [
+                { "type": "command", "command": "/usr/bin/agent-toast-send --event task_complete" },
+                { "type": "command", "command": "/opt/agent-toast-wrapper.sh" }
+            ] },
+            { "hooks": [{ "type": "command", "command": "agent-toast-send --agent-toast-managed" }] }
+        ] } });
+        assert_eq!(migrate_legacy_hooks(&mut root), 1);
+        assert_eq!(
+            root["hooks"]["Stop"][0]["hooks"][0]["command"],
+            "/usr/bin/agent-toast-send --event task_complete --agent-toast-managed"
+        );
+        assert_eq!(
+            root["hooks"]["Stop"][0]["hooks"][1]["command"],
+            "/opt/agent-toast-wrapper.sh"
+        );
+        assert_eq!(migrate_legacy_hooks(&mut root), 0);
+    }
+
+    #[test]
+    fn remove_keeps_unmarked_commands_sharing_a_group() {
+        let root = json!({ "hooks": { "Stop": [
+            { "hooks": [
+                { "type": "command", "command": "agent-toast-send --event task_complete" },
+                { "type": "command", "command": "/opt/agent-toast-wrapper.sh" }
+            ] },
+            { "hooks": [{ "type": "command", "command": "\"C:\\Agent Toast\\Agent Toast.exe\" --daemon" }] }
+        ] } });
+        let cleaned = remove_agent_toast_hooks(root);
+        assert_eq!(
+            cleaned["hooks"]["Stop"],
+            json!([{ "hooks": [{ "type": "command", "command": "/opt/agent-toast-wrapper.sh" }] }])
+        );
+    }
+
     #[test]
     fn is_agent_toast_cmd_detects_product_name_with_space() {
         // Windows installer productName: "Agent Toast"
@@ -156,6 +663,35 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn event_for_maps_hooks_and_matchers() {
+        assert_eq!(event_for("Stop", None), Some("task_complete"));
+        assert_eq!(event_for("SessionStart", None), Some("session_start"));
+        assert_eq!(event_for("PostToolUseFailure", None), Some("error"));
+        assert_eq!(
+            event_for("Notification", Some("permission_prompt")),
+            Some("user_input_required")
+        );
+        // Unknown notification types still ask for input.
+        assert_eq!(
+            event_for("Notification", Some("auth_success")),
+            Some("user_input_required")
+        );
+        assert_eq!(event_for("Notification", None), Some("user_input_required"));
+        assert_eq!(event_for("stop", None), None);
+        assert_eq!(event_for("BrandNewHook", None), None);
+    }
+
+    #[test]
+    fn event_from_hook_reads_payload() {
+        let hook = json!({"hook_event_name": "Notification", "notification_type": "idle_prompt"});
+        assert_eq!(event_from_hook(&hook), Some("user_input_required"));
+        let hook = json!({"session_id": "s", "hook_event_name": " Stop "});
+        assert_eq!(event_from_hook(&hook), Some("task_complete"));
+        assert_eq!(event_from_hook(&json!({"session_id": "s"})), None);
+        assert_eq!(event_from_hook(&json!({"hook_event_name": 3})), None);
+    }
+
     #[test]
     fn merge_into_empty_root_creates_hooks_object() {
         let root = json!({});
@@ -163,13 +699,79 @@ mod tests {
             event_key: "Stop",
             matcher: None,
             command: "agent-toast-send --url http://x --event task_complete".to_string(),
+            ..HookEntry::default()
         }];
         let merged = merge_agent_toast_hooks(root, &entries);
         let cmd = &merged["hooks"]["Stop"][0]["hooks"][0]["command"];
         assert_eq!(
             cmd.as_str().unwrap(),
-            "agent-toast-send --url http://x --event task_complete"
+            "agent-toast-send --url http://x --event task_complete --agent-toast-managed"
+        );
+    }
+
+    #[test]
+    fn merge_writes_timeout_and_async_options() {
+        let entries = vec![
+            HookEntry {
+                event_key: "Stop",
+                command: "agent-toast-send --event task_complete".into(),
+                timeout: Some(DEFAULT_TIMEOUT_SECS),
+                run_async: true,
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "PermissionRequest",
+                command: "agent-toast-send decide".into(),
+                timeout: Some(waiting_timeout(50, DEFAULT_TIMEOUT_SECS)),
+                ..HookEntry::default()
+            },
+        ];
+        let merged = merge_agent_toast_hooks(json!({}), &entries);
+        let stop = &merged["hooks"]["Stop"][0]["hooks"][0];
+        assert_eq!(stop["timeout"], 10);
+        assert_eq!(stop["async"], true);
+        let decide = &merged["hooks"]["PermissionRequest"][0]["hooks"][0];
+        assert_eq!(decide["timeout"], 60);
+        assert!(decide.get("async").is_none(), "{decide}");
+
+        let plain = merge_agent_toast_hooks(
+            json!({}),
+            &[HookEntry {
+                event_key: "Stop",
+                command: "agent-toast-send".into(),
+                ..HookEntry::default()
+            }],
         );
+        let inner = plain["hooks"]["Stop"][0]["hooks"][0].as_object().unwrap();
+        assert_eq!(
+            inner.keys().collect::<Vec<_>>(),
+            ["type", "command"],
+            "no options unless set"
+        );
+        assert_eq!(waiting_timeout(u64::MAX, 10), u32::MAX);
+    }
+
+    #[test]
+    fn diff_reports_option_changes() {
+        let entry = |timeout| HookEntry {
+            event_key: "Stop",
+            command: "agent-toast-send --event task_complete".into(),
+            timeout,
+            ..HookEntry::default()
+        };
+        let before = merge_agent_toast_hooks(json!({}), &[entry(None)]);
+        let after = merge_agent_toast_hooks(before.clone(), &[entry(Some(10))]);
+        let diff = diff_hooks(&before, &after);
+        assert_eq!(diff.entries.len(), 1);
+        let cmd = "agent-toast-send --event task_complete --agent-toast-managed";
+        assert_eq!(
+            diff.entries[0].change,
+            HookChange::Changed {
+                before: cmd.to_string(),
+                after: format!("{cmd} (timeout 10s)"),
+            }
+        );
+        assert!(diff_hooks(&after, &after.clone()).is_empty());
     }
 
     #[test]
@@ -188,6 +790,7 @@ mod tests {
             event_key: "Stop",
             matcher: None,
             command: "agent-toast-send --url http://x --event task_complete".to_string(),
+            ..HookEntry::default()
         }];
         let merged = merge_agent_toast_hooks(root, &entries);
 
@@ -221,6 +824,7 @@ mod tests {
             event_key: "Stop",
             matcher: None,
             command: "agent-toast-send --url http://x --event task_complete".to_string(),
+            ..HookEntry::default()
         }];
         let merged = merge_agent_toast_hooks(root, &entries);
         let stop_arr = merged["hooks"]["Stop"].as_array().unwrap();
@@ -250,6 +854,7 @@ mod tests {
             event_key: "Notification",
             matcher: Some("permission_prompt"),
             command: "agent-toast-send --url http://x --event user_input_required".to_string(),
+            ..HookEntry::default()
         }];
         let merged = merge_agent_toast_hooks(root, &entries);
         assert_eq!(
@@ -298,11 +903,13 @@ mod tests {
                 event_key: "Stop",
                 matcher: None,
                 command: "agent-toast-send --event task_complete".into(),
+                ..HookEntry::default()
             },
             HookEntry {
                 event_key: "Notification",
                 matcher: Some("permission_prompt"),
                 command: "agent-toast-send --event user_input_required".into(),
+                ..HookEntry::default()
             },
         ];
         let merged = merge_agent_toast_hooks(root, &entries);
@@ -330,6 +937,7 @@ mod tests {
             event_key: "Stop",
             matcher: None,
             command: "agent-toast-send --url http://x --event task_complete".to_string(),
+            ..HookEntry::default()
         }];
         let merged = merge_agent_toast_hooks(root, &entries);
         // Stop has the one new entry.
@@ -374,4 +982,181 @@ mod tests {
             "SessionStart had only agent-toast — now empty"
         );
     }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let before = json!({
+            "hooks": {
+                "Stop": [
+                    {"hooks":[{"type":"command","command":"agent-toast-send --event task_complete"}]},
+                    {"hooks":[{"type":"command","command":"/usr/bin/custom"}]}
+                ],
+                "SessionStart": [
+                    {"hooks":[{"type":"command","command":"agent-toast-send --event session_start"}]}
+                ]
+            }
+        });
+        let entries = vec![
+            HookEntry {
+                event_key: "Stop",
+                matcher: None,
+                command: "agent-toast-send --url http://x --event task_complete".into(),
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "Notification",
+                matcher: Some("permission_prompt"),
+                command: "agent-toast-send --event user_input_required".into(),
+                ..HookEntry::default()
+            },
+        ];
+        let after = merge_agent_toast_hooks(before.clone(), &entries);
+        let diff = diff_hooks(&before, &after);
+
+        assert_eq!(
+            diff.entries,
+            vec![
+                HookDiffEntry {
+                    event_key: "Notification".into(),
+                    matcher: Some("permission_prompt".into()),
+                    change: HookChange::Added {
+                        command: "agent-toast-send --event user_input_required --agent-toast-managed".into()
+                    },
+                },
+                HookDiffEntry {
+                    event_key: "SessionStart".into(),
+                    matcher: None,
+                    change: HookChange::Removed {
+                        command: "agent-toast-send --event session_start".into()
+                    },
+                },
+                HookDiffEntry {
+                    event_key: "Stop".into(),
+                    matcher: None,
+                    change: HookChange::Changed {
+                        before: "agent-toast-send --event task_complete".into(),
+                        after: "agent-toast-send --url http://x --event task_complete --agent-toast-managed".into(),
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "Notification [permission_prompt]:\n  + agent-toast-send --event user_input_required --agent-toast-managed\n\
+             SessionStart:\n  - agent-toast-send --event session_start\n\
+             Stop:\n  ~ agent-toast-send --event task_complete\n    → agent-toast-send --url http://x --event task_complete --agent-toast-managed\n"
+        );
+    }
+
+    #[test]
+    fn diff_of_identical_hooks_is_empty() {
+        let root = json!({
+            "hooks": {
+                "Stop": [
+                    {"hooks":[{"type":"command","command":"agent-toast-send --event task_complete"}]}
+                ]
+            }
+        });
+        let diff = diff_hooks(&root, &root.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+        assert!(diff_hooks(&json!({}), &json!({"hooks": {}})).is_empty());
+    }
+
+    #[test]
+    fn diff_serializes_with_kind_tag() {
+        let diff = diff_hooks(
+            &json!({}),
+            &json!({"hooks": {"Stop": [{"hooks": [{"type": "command", "command": "a"}]}]}}),
+        );
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap(),
+            json!({"entries": [{"event_key": "Stop", "matcher": null, "kind": "added", "command": "a"}]})
+        );
+    }
+
+    #[test]
+    fn scope_paths_and_names() {
+        let home = Path::new("/home/u");
+        let project = Path::new("/src/app");
+        assert_eq!(
+            Scope::User.settings_path(home, project),
+            Path::new("/home/u/.claude/settings.json")
+        );
+        assert_eq!(
+            Scope::Project.settings_path(home, project),
+            Path::new("/src/app/.claude/settings.json")
+        );
+        assert_eq!(
+            Scope::Local.settings_path(home, project),
+            Path::new("/src/app/.claude/settings.local.json")
+        );
+        for scope in Scope::ALL {
+            assert_eq!(scope.as_str().parse::<Scope>(), Ok(scope));
+        }
+        assert!("global".parse::<Scope>().is_err());
+        assert_eq!(serde_json::to_value(Scope::Local).unwrap(), json!("local"));
+    }
+
+    #[test]
+    fn duplicate_hooks_reports_events_in_several_scopes() {
+        let user = merge_agent_toast_hooks(
+            json!({}),
+            &[
+                HookEntry {
+                    event_key: "Stop",
+                    matcher: None,
+                    command: "agent-toast-send --event task_complete".into(),
+                    ..HookEntry::default()
+                },
+                HookEntry {
+                    event_key: "Notification",
+                    matcher: Some("permission_prompt"),
+                    command: "agent-toast-send --event user_input_required".into(),
+                    ..HookEntry::default()
+                },
+            ],
+        );
+        let project = merge_agent_toast_hooks(
+            json!({ "hooks": { "Stop": [
+                { "hooks": [{ "type": "command", "command": "/usr/bin/lint" }] }
+            ] } }),
+            &[
+                HookEntry {
+                    event_key: "Stop",
+                    matcher: None,
+                    command: "agent-toast-send --dynamic".into(),
+                    ..HookEntry::default()
+                },
+                HookEntry {
+                    event_key: "Notification",
+                    matcher: Some("idle"),
+                    command: "agent-toast-send".into(),
+                    ..HookEntry::default()
+                },
+            ],
+        );
+        let local = json!({ "hooks": { "PreToolUse": [
+            { "hooks": [{ "type": "command", "command": "agent-toast-send" }] }
+        ] } });
+
+        let dups = duplicate_hooks(&[
+            (Scope::Local, &local),
+            (Scope::Project, &project),
+            (Scope::User, &user),
+        ]);
+        assert_eq!(
+            dups,
+            vec![DuplicateHook {
+                event_key: "Stop".into(),
+                matcher: None,
+                scopes: vec![Scope::User, Scope::Project],
+            }]
+        );
+        assert_eq!(
+            dups[0].to_string(),
+            "Stop is registered in user and project settings; each will show a toast"
+        );
+        assert!(duplicate_hooks(&[(Scope::User, &user)]).is_empty());
+    }
 }
@@ -0,0 +1,561 @@
+//! Format-preserving rewrites of JSON files the user owns (`settings.json`).
+//!
+//! Callers edit a parsed [`Value`] as before and hand the result to
+//! [`rewrite`] together with the file's current text. Every object member or
+//! array element whose value didn't change is copied from the original text
+//! byte for byte, so key order, indentation, line endings and hand formatting
+//! survive, and a dotfiles diff shows only what agent-toast changed. New or
+//! changed values are pretty-printed in the file's own indentation style,
+//! with keys in insertion order (the workspace enables serde_json's
+//! `preserve_order`), so new hook entries read `type` before `command` the
+//! way Claude Code writes them.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::ops::Range;
+
+/// Serialize `new` as the next version of a file whose current text is
+/// `original`, keeping every untouched region of `original`.
+///
+/// Falls back to plain `to_string_pretty` when `original` is empty or isn't
+/// valid JSON (there is no formatting worth keeping).
+pub fn rewrite(original: &str, new: &Value) -> String {
+    let fresh = || serde_json::to_string_pretty(new).unwrap_or_default();
+    let Ok(old) = serde_json::from_str::<Value>(original) else {
+        return fresh();
+    };
+    let mut scanner = Scanner {
+        text: original,
+        pos: 0,
+    };
+    let Some(root) = scanner.value() else {
+        return fresh();
+    };
+    let style = Style::detect(original);
+    let mut out = String::with_capacity(original.len());
+    out.push_str(&original[..root.span.start]);
+    Patcher {
+        text: original,
+        style: &style,
+    }
+    .patch(&mut out, &root, &old, new, 0);
+    out.push_str(&original[root.span.end..]);
+    out
+}
+
+/// Indentation unit and line ending of the original file.
+struct Style {
+    indent: String,
+    newline: &'static str,
+}
+
+impl Style {
+    /// The whitespace opening the first indented line is one level (the
+    /// root's first member). Compact files get two spaces.
+    fn detect(text: &str) -> Self {
+        let indent = text
+            .split('\n')
+            .skip(1)
+            .map(|line| {
+                let body = line.trim_start_matches([' ', '\t']);
+                (
+                    &line[..line.len() - body.len()],
+                    body.trim_end_matches('\r'),
+                )
+            })
+            .find(|(ws, body)| !ws.is_empty() && !body.is_empty())
+            .map(|(ws, _)| ws.to_string())
+            .unwrap_or_else(|| "  ".into());
+        let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+        Self { indent, newline }
+    }
+
+    fn line_break(&self, depth: usize) -> String {
+        format!("{}{}", self.newline, self.indent.repeat(depth))
+    }
+}
+
+/// Byte spans of one parsed JSON value and its children.
+struct Node {
+    span: Range<usize>,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Object(Vec<Member>),
+    Array(Vec<Node>),
+    Scalar,
+}
+
+struct Member {
+    key: String,
+    key_span: Range<usize>,
+    value: Node,
+}
+
+/// Minimal JSON scanner recording spans. Only runs on text `serde_json`
+/// already accepted, so it bails out (`None`) rather than reporting errors.
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> Option<()> {
+        self.skip_ws();
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn value(&mut self) -> Option<Node> {
+        self.skip_ws();
+        let start = self.pos;
+        let kind = match self.peek()? {
+            b'{' => NodeKind::Object(self.members()?),
+            b'[' => NodeKind::Array(self.items()?),
+            b'"' => {
+                self.string()?;
+                NodeKind::Scalar
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b']' | b'}' | b' ' | b'\t' | b'\n' | b'\r')
+                ) {
+                    self.pos += 1;
+                }
+                NodeKind::Scalar
+            }
+        };
+        (self.pos > start).then_some(Node {
+            span: start..self.pos,
+            kind,
+        })
+    }
+
+    fn string(&mut self) -> Option<Range<usize>> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Some(start..self.pos);
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn members(&mut self) -> Option<Vec<Member>> {
+        self.eat(b'{')?;
+        let mut members = Vec::new();
+        if self.eat(b'}').is_some() {
+            return Some(members);
+        }
+        loop {
+            self.skip_ws();
+            let key_span = self.string()?;
+            let key = serde_json::from_str(&self.text[key_span.clone()]).ok()?;
+            self.eat(b':')?;
+            let value = self.value()?;
+            members.push(Member {
+                key,
+                key_span,
+                value,
+            });
+            if self.eat(b',').is_none() {
+                self.eat(b'}')?;
+                return Some(members);
+            }
+        }
+    }
+
+    fn items(&mut self) -> Option<Vec<Node>> {
+        self.eat(b'[')?;
+        let mut items = Vec::new();
+        if self.eat(b']').is_some() {
+            return Some(items);
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(b',').is_none() {
+                self.eat(b']')?;
+                return Some(items);
+            }
+        }
+    }
+}
+
+/// How a rebuilt object or array is laid out, copied from its original.
+enum Layout {
+    /// One entry per line (also used for containers that were empty).
+    Block,
+    /// All on one line: the padding after the opening bracket, between
+    /// entries (after the comma) and before the closing bracket.
+    Inline {
+        open: String,
+        sep: String,
+        close: String,
+    },
+}
+
+/// Where the text for one element of a rewritten array comes from.
+enum Source {
+    /// An identical element of the original, copied verbatim.
+    Same(usize),
+    /// The original element at the same index, patched.
+    Patch,
+    /// Nothing to reuse; pretty-printed.
+    Fresh,
+}
+
+struct Patcher<'a> {
+    text: &'a str,
+    style: &'a Style,
+}
+
+impl Patcher<'_> {
+    /// Append the text for `new`, whose previous version `old` was written
+    /// as `node`, at nesting `depth`.
+    fn patch(&self, out: &mut String, node: &Node, old: &Value, new: &Value, depth: usize) {
+        if old == new {
+            out.push_str(&self.text[node.span.clone()]);
+            return;
+        }
+        match (&node.kind, old, new) {
+            (NodeKind::Object(members), Value::Object(old_map), Value::Object(new_map)) => {
+                self.patch_object(out, node, members, old_map, new_map, depth)
+            }
+            (NodeKind::Array(items), Value::Array(old_items), Value::Array(new_items)) => {
+                self.patch_array(out, node, items, old_items, new_items, depth)
+            }
+            _ => out.push_str(&self.fresh(new, depth)),
+        }
+    }
+
+    fn patch_object(
+        &self,
+        out: &mut String,
+        node: &Node,
+        members: &[Member],
+        old: &serde_json::Map<String, Value>,
+        new: &serde_json::Map<String, Value>,
+        depth: usize,
+    ) {
+        // Same keys: only values changed, so splice them in place and keep
+        // every separator as written.
+        let same_keys =
+            members.len() == new.len() && members.iter().all(|m| new.contains_key(&m.key));
+        if same_keys {
+            let mut cursor = node.span.start;
+            for m in members {
+                out.push_str(&self.text[cursor..m.value.span.start]);
+                self.patch(out, &m.value, &old[&m.key], &new[&m.key], depth + 1);
+                cursor = m.value.span.end;
+            }
+            out.push_str(&self.text[cursor..node.span.end]);
+            return;
+        }
+
+        // Keys added or removed: relay the members, keeping the original
+        // order and each untouched member's text, new keys last.
+        let colon = members
+            .first()
+            .map(|m| &self.text[m.key_span.end..m.value.span.start])
+            .unwrap_or(": ");
+        let layout = self.layout_of(
+            node,
+            members.iter().map(|m| (m.key_span.start, m.value.span.end)),
+            if colon.ends_with(' ') { ", " } else { "," },
+        );
+        let mut parts = Vec::with_capacity(new.len());
+        for m in members.iter().filter(|m| new.contains_key(&m.key)) {
+            let mut value = String::new();
+            self.patch(&mut value, &m.value, &old[&m.key], &new[&m.key], depth + 1);
+            parts.push(format!("{}{colon}{value}", &self.text[m.key_span.clone()]));
+        }
+        for (key, value) in new
+            .iter()
+            .filter(|(k, _)| !members.iter().any(|m| &m.key == *k))
+        {
+            let key = serde_json::to_string(key).unwrap_or_default();
+            let value = self.fresh_in(&layout, value, depth + 1);
+            parts.push(format!("{key}{colon}{value}"));
+        }
+        out.push_str(&self.join('{', '}', &layout, &parts, depth));
+    }
+
+    fn patch_array(
+        &self,
+        out: &mut String,
+        node: &Node,
+        items: &[Node],
+        old: &[Value],
+        new: &[Value],
+        depth: usize,
+    ) {
+        let mut used = vec![false; old.len()];
+        let mut sources: Vec<Source> = new
+            .iter()
+            .map(
+                |v| match (0..old.len()).find(|&i| !used[i] && old[i] == *v) {
+                    Some(i) => {
+                        used[i] = true;
+                        Source::Same(i)
+                    }
+                    None => Source::Fresh,
+                },
+            )
+            .collect();
+        for (j, source) in sources.iter_mut().enumerate() {
+            if matches!(source, Source::Fresh) && j < old.len() && !used[j] {
+                used[j] = true;
+                *source = Source::Patch;
+            }
+        }
+
+        // Every element still at its index: splice in place.
+        let in_place = old.len() == new.len()
+            && sources.iter().enumerate().all(|(j, s)| {
+                matches!(s, Source::Patch) || matches!(s, Source::Same(i) if *i == j)
+            });
+        if in_place {
+            let mut cursor = node.span.start;
+            for (j, item) in items.iter().enumerate() {
+                out.push_str(&self.text[cursor..item.span.start]);
+                self.patch(out, item, &old[j], &new[j], depth + 1);
+                cursor = item.span.end;
+            }
+            out.push_str(&self.text[cursor..node.span.end]);
+            return;
+        }
+
+        let layout = self.layout_of(node, items.iter().map(|i| (i.span.start, i.span.end)), ", ");
+        let parts: Vec<String> = sources
+            .iter()
+            .enumerate()
+            .map(|(j, source)| match source {
+                Source::Same(i) => self.text[items[*i].span.clone()].to_string(),
+                Source::Patch => {
+                    let mut value = String::new();
+                    self.patch(&mut value, &items[j], &old[j], &new[j], depth + 1);
+                    value
+                }
+                Source::Fresh => self.fresh_in(&layout, &new[j], depth + 1),
+            })
+            .collect();
+        out.push_str(&self.join('[', ']', &layout, &parts, depth));
+    }
+
+    /// How the container `node` was written. `parts` are the byte ranges of
+    /// its entries; `default_sep` separates entries of a one-entry inline
+    /// container.
+    fn layout_of(
+        &self,
+        node: &Node,
+        mut parts: impl Iterator<Item = (usize, usize)>,
+        default_sep: &'static str,
+    ) -> Layout {
+        let text = &self.text[node.span.clone()];
+        let Some(first) = parts.next() else {
+            return Layout::Block;
+        };
+        if text.contains('\n') {
+            return Layout::Block;
+        }
+        let second = parts.next();
+        let sep = second.map_or(default_sep.to_string(), |next| {
+            self.text[first.1..next.0].to_string()
+        });
+        let last_end = parts.last().or(second).unwrap_or(first).1;
+        Layout::Inline {
+            open: self.text[node.span.start + 1..first.0].to_string(),
+            sep,
+            close: self.text[last_end..node.span.end - 1].to_string(),
+        }
+    }
+
+    /// Brackets around `parts`: on one line for an inline container,
+    /// otherwise one part per line, indented one level deeper.
+    fn join(
+        &self,
+        open: char,
+        close: char,
+        layout: &Layout,
+        parts: &[String],
+        depth: usize,
+    ) -> String {
+        if parts.is_empty() {
+            return format!("{open}{close}");
+        }
+        match layout {
+            Layout::Inline {
+                open: pad_open,
+                sep,
+                close: pad_close,
+            } => format!("{open}{pad_open}{}{pad_close}{close}", parts.join(sep)),
+            Layout::Block => {
+                let inner = self.style.line_break(depth + 1);
+                let mut s = String::from(open);
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        s.push(',');
+                    }
+                    s.push_str(&inner);
+                    s.push_str(part);
+                }
+                s.push_str(&self.style.line_break(depth));
+                s.push(close);
+                s
+            }
+        }
+    }
+
+    /// A new value inside a container laid out as `layout`: compact on a
+    /// single line, pretty-printed in a block.
+    fn fresh_in(&self, layout: &Layout, value: &Value, depth: usize) -> String {
+        match layout {
+            Layout::Inline { .. } => serde_json::to_string(value).unwrap_or_default(),
+            Layout::Block => self.fresh(value, depth),
+        }
+    }
+
+    /// `value` pretty-printed in the file's style, its continuation lines
+    /// indented for `depth`.
+    fn fresh(&self, value: &Value, depth: usize) -> String {
+        let mut buf = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.style.indent.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+        if value.serialize(&mut ser).is_err() {
+            return String::new();
+        }
+        // Raw newlines only separate tokens; those inside strings are escaped.
+        String::from_utf8(buf)
+            .unwrap_or_default()
+            .replace('\n', &self.style.line_break(depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn edit(original: &str, f: impl FnOnce(&mut Value)) -> String {
+        let mut v: Value = serde_json::from_str(original).unwrap();
+        f(&mut v);
+        let out = rewrite(original, &v);
+        assert_eq!(serde_json::from_str::<Value>(&out).unwrap(), v);
+        out
+    }
+
+    #[test]
+    fn unchanged_value_is_byte_identical() {
+        let original = "{\n    \"b\": [1,2,   3],\n    \"a\": {\"x\": true}\n}\n";
+        assert_eq!(edit(original, |_| {}), original);
+    }
+
+    #[test]
+    fn changed_scalar_keeps_order_and_neighbours() {
+        let original = "{\n    \"zeta\": 1,\n    \"agent_toast\": {\"theme\": \"dark\"},\n    \"alpha\": [1,2]\n}\n";
+        let out = edit(original, |v| v["agent_toast"]["theme"] = json!("light"));
+        assert_eq!(
+            out,
+            "{\n    \"zeta\": 1,\n    \"agent_toast\": {\"theme\": \"light\"},\n    \"alpha\": [1,2]\n}\n"
+        );
+    }
+
+    #[test]
+    fn new_key_appended_in_file_indent() {
+        let original = "{\n\t\"model\": \"opus\"\n}";
+        let out = edit(original, |v| v["agent_toast"] = json!({"theme": "dark"}));
+        assert_eq!(
+            out,
+            "{\n\t\"model\": \"opus\",\n\t\"agent_toast\": {\n\t\t\"theme\": \"dark\"\n\t}\n}"
+        );
+    }
+
+    #[test]
+    fn removed_key_drops_only_that_member() {
+        let original = "{\n  \"a\": 1,\n  \"b\": [ 1, 2 ],\n  \"c\": 3\n}";
+        let out = edit(original, |v| {
+            v.as_object_mut().unwrap().remove("a");
+        });
+        assert_eq!(out, "{\n  \"b\": [ 1, 2 ],\n  \"c\": 3\n}");
+    }
+
+    #[test]
+    fn array_reuses_untouched_elements_verbatim() {
+        let original = r#"{
+  "hooks": {
+    "Stop": [
+      {"hooks": [{"type": "command", "command": "agent-toast-send --event old"}]},
+      { "hooks": [ { "type": "command", "command": "/usr/bin/custom" } ] }
+    ]
+  }
+}
+"#;
+        let out = edit(original, |v| {
+            v["hooks"]["Stop"] = json!([
+                {"hooks": [{"type": "command", "command": "/usr/bin/custom"}]},
+                {"hooks": [{"type": "command", "command": "agent-toast-send --event new"}]}
+            ]);
+        });
+        // The custom entry keeps its spacing, the agent-toast entry is patched
+        // in place of the one it replaced.
+        assert!(
+            out.contains(r#"{ "hooks": [ { "type": "command", "command": "/usr/bin/custom" } ] }"#)
+        );
+        assert!(out.contains("agent-toast-send --event new"));
+        assert!(out.ends_with("}\n"));
+    }
+
+    #[test]
+    fn inline_containers_stay_inline() {
+        let original = r#"{"agent_toast":{"locale":"ko"},"otherTool":{"x":1}}"#;
+        let out = edit(original, |v| v["agent_toast"]["theme"] = json!("dark"));
+        assert_eq!(
+            out,
+            r#"{"agent_toast":{"locale":"ko","theme":"dark"},"otherTool":{"x":1}}"#
+        );
+
+        let original = "{\n  \"list\": [ 1, 2, 3 ]\n}";
+        let out = edit(original, |v| v["list"] = json!([3, 1, {"a": 1}]));
+        assert_eq!(out, "{\n  \"list\": [ 3, 1, {\"a\":1} ]\n}");
+    }
+
+    #[test]
+    fn crlf_is_kept_for_new_lines() {
+        let original = "{\r\n  \"a\": 1\r\n}\r\n";
+        let out = edit(original, |v| v["b"] = json!([true]));
+        assert_eq!(
+            out,
+            "{\r\n  \"a\": 1,\r\n  \"b\": [\r\n    true\r\n  ]\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn empty_or_invalid_original_is_pretty_printed() {
+        let v = json!({"a": 1});
+        assert_eq!(rewrite("", &v), "{\n  \"a\": 1\n}");
+        assert_eq!(rewrite("{ not json", &v), "{\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn escaped_strings_and_type_changes() {
+        let original = "{\"k\\\"ey\": \"a\\\\\\\"b\", \"n\": [1]}";
+        let out = edit(original, |v| v["n"] = json!("x"));
+        assert_eq!(out, "{\"k\\\"ey\": \"a\\\\\\\"b\", \"n\": \"x\"}");
+    }
+}
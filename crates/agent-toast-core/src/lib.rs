@@ -1,8 +1,19 @@
 //! Shared types and helpers for Agent Toast — used by both the desktop app
 //! and the remote `agent-toast-send` CLI.
 
+pub mod backup;
+pub mod codex;
+pub mod codex_config;
 pub mod dynamic;
+pub mod frame;
 pub mod hook_config;
+pub mod json_edit;
+pub mod markdown;
+pub mod permission;
+pub mod schema;
+pub mod source;
+pub mod template;
+pub mod transcript;
 pub mod wire;
 
 pub use wire::NotifyRequest;
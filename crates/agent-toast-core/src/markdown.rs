@@ -0,0 +1,288 @@
+//! Markdown → plain text for toast bodies.
+//!
+//! Agent messages are Markdown, but a toast has two lines of plain text.
+//! [`to_plain_text`] drops the syntax and keeps the words: emphasis markers,
+//! backticks, heading/quote/list prefixes and link targets go away, fenced
+//! code blocks collapse to [`CODE_MARKER`], and everything is joined onto one
+//! line. It is a best-effort line scanner, not a CommonMark parser.
+
+/// Stand-in for a fenced code block.
+pub const CODE_MARKER: &str = "[code]";
+
+/// Convert Markdown to a single line of plain text.
+pub fn to_plain_text(markdown: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some((ch, len)) = fence {
+            if fence_of(trimmed).is_some_and(|(c, l)| c == ch && l >= len)
+                && trimmed.trim_start_matches(ch).trim().is_empty()
+            {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = fence_of(trimmed) {
+            fence = Some(open);
+            parts.push(CODE_MARKER.to_string());
+            continue;
+        }
+        if is_thematic_break(trimmed) {
+            continue;
+        }
+        let text = inline_to_plain(strip_block_prefix(trimmed));
+        if !text.is_empty() {
+            parts.push(text);
+        }
+    }
+    collapse_whitespace(&parts.join(" "))
+}
+
+/// ```` ``` ```` / `~~~` (3+) opening a fenced block: the fence char and length.
+fn fence_of(line: &str) -> Option<(char, usize)> {
+    let ch = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|c| *c == ch).count();
+    (len >= 3).then_some((ch, len))
+}
+
+fn is_thematic_break(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|m| compact.chars().all(|c| c == *m))
+}
+
+/// Strip quote markers, then one heading / bullet / task-box prefix.
+fn strip_block_prefix(mut line: &str) -> &str {
+    while let Some(rest) = line.strip_prefix('>') {
+        line = rest.trim_start();
+    }
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&hashes) {
+        let rest = &line[hashes..];
+        if rest.is_empty() || rest.starts_with(' ') {
+            return rest.trim().trim_end_matches('#').trim_end();
+        }
+    }
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            line = rest.trim_start();
+            for task in ["[ ] ", "[x] ", "[X] "] {
+                if let Some(rest) = line.strip_prefix(task) {
+                    return rest;
+                }
+            }
+            return line;
+        }
+    }
+    line
+}
+
+/// Inline syntax: code spans, images, links, autolinks, emphasis, escapes.
+fn inline_to_plain(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(|n| n.is_ascii_punctuation()) => {
+                out.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|c| **c == '`').count();
+                match find_run(&chars, i + run, '`', run) {
+                    Some(close) => {
+                        let code: String = chars[i + run..close].iter().collect();
+                        out.push_str(code.trim());
+                        i = close + run;
+                    }
+                    None => i += run,
+                }
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => match link_at(&chars, i + 1) {
+                Some((text, end)) => {
+                    out.push_str(&inline_to_plain(&text));
+                    i = end;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '[' => match link_at(&chars, i) {
+                Some((text, end)) => {
+                    out.push_str(&inline_to_plain(&text));
+                    i = end;
+                }
+                None => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '<' => {
+                let close = chars[i..].iter().position(|c| *c == '>').map(|p| i + p);
+                let inner: Option<String> = close.map(|e| chars[i + 1..e].iter().collect());
+                match inner.filter(|s| s.contains("://") && !s.contains(' ')) {
+                    Some(url) => {
+                        out.push_str(&url);
+                        i = close.unwrap() + 1;
+                    }
+                    None => {
+                        out.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '*' | '_' | '~' => {
+                let run = chars[i..].iter().take_while(|x| **x == c).count();
+                let before = i.checked_sub(1).map(|p| chars[p]);
+                let after = chars.get(i + run).copied();
+                if is_emphasis_delimiter(c, run, before, after) {
+                    i += run;
+                } else {
+                    out.extend(std::iter::repeat_n(c, run));
+                    i += run;
+                }
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Index of the next run of exactly `len` `ch`s at or after `from`.
+fn find_run(chars: &[char], from: usize, ch: char, len: usize) -> Option<usize> {
+    let mut i = from;
+    while i < chars.len() {
+        if chars[i] == ch {
+            let run = chars[i..].iter().take_while(|c| **c == ch).count();
+            if run == len {
+                return Some(i);
+            }
+            i += run;
+        } else {
+            i += 1;
+        }
+    }
+    None
+}
+
+/// `[text](target)` or `[text][ref]` starting at `open`: the text and the
+/// index just past the link.
+fn link_at(chars: &[char], open: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    for (j, c) in chars.iter().enumerate().skip(open) {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(j);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let text: String = chars[open + 1..close].iter().collect();
+    let closer = match chars.get(close + 1) {
+        Some('(') => ')',
+        Some('[') => ']',
+        _ => return None,
+    };
+    let end = chars[close + 2..].iter().position(|c| *c == closer)? + close + 2;
+    Some((text, end + 1))
+}
+
+/// Whether a run of `*`, `_` or `~` is emphasis rather than a literal: it
+/// must touch a word on one side and whitespace/punctuation/edge on the other.
+/// `_` inside a word (`snake_case`) and `~` runs other than `~~` are literal.
+fn is_emphasis_delimiter(c: char, run: usize, before: Option<char>, after: Option<char>) -> bool {
+    if c == '~' && run != 2 {
+        return false;
+    }
+    if run > 3 {
+        return false;
+    }
+    let word = |x: Option<char>| x.is_some_and(|x| !x.is_whitespace());
+    let edge = |x: Option<char>| x.is_none_or(|x| x.is_whitespace() || x.is_ascii_punctuation());
+    if c == '_'
+        && before.is_some_and(char::is_alphanumeric)
+        && after.is_some_and(char::is_alphanumeric)
+    {
+        return false;
+    }
+    (edge(before) && word(after)) || (word(before) && edge(after))
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_emphasis_and_code_spans() {
+        assert_eq!(
+            to_plain_text("**Done**: fixed `parse_args` in _two_ places ~~today~~"),
+            "Done: fixed parse_args in two places today"
+        );
+    }
+
+    #[test]
+    fn keeps_literal_symbols() {
+        assert_eq!(
+            to_plain_text("2 * 3 = 6 and snake_case_name"),
+            "2 * 3 = 6 and snake_case_name"
+        );
+        assert_eq!(to_plain_text(r"escaped \*stars\*"), "escaped *stars*");
+        assert_eq!(to_plain_text("~/src and a ~ b"), "~/src and a ~ b");
+    }
+
+    #[test]
+    fn collapses_fenced_code() {
+        let md = "Run this:\n\n```bash\ncargo test\n# not a heading\n```\nThen **push**.";
+        assert_eq!(to_plain_text(md), "Run this: [code] Then push.");
+        assert_eq!(to_plain_text("~~~\nunclosed"), "[code]");
+    }
+
+    #[test]
+    fn strips_block_prefixes() {
+        let md = "# Summary\n\n> quoted\n- [x] tests pass\n* item\n1. first\n---\n## Next ##";
+        assert_eq!(
+            to_plain_text(md),
+            "Summary quoted tests pass item 1. first Next"
+        );
+    }
+
+    #[test]
+    fn links_keep_their_text() {
+        assert_eq!(
+            to_plain_text(
+                "See [the PR](https://x/pr/1) and ![diagram](a.png) or <https://example.com>"
+            ),
+            "See the PR and diagram or https://example.com"
+        );
+        assert_eq!(
+            to_plain_text("[ref link][1] and [not a link]"),
+            "ref link and [not a link]"
+        );
+    }
+
+    #[test]
+    fn plain_text_is_unchanged() {
+        assert_eq!(to_plain_text("빌드 완료 🎉"), "빌드 완료 🎉");
+        assert_eq!(to_plain_text(""), "");
+    }
+}
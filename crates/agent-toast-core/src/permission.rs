@@ -0,0 +1,172 @@
+//! Answering Claude Code `PermissionRequest` hooks from the toast.
+//!
+//! `decide` mode shows a toast with Allow / Deny buttons, blocks until the
+//! sender learns which one was clicked, and prints the hook decision JSON
+//! Claude Code reads from the hook's stdout. A dismissed toast or a timeout
+//! maps to [`Decision::Ask`], which prints nothing so Claude Code falls back to
+//! its normal in-terminal prompt.
+
+use crate::dynamic::{extract_tool_description, normalize_message, summarize_tool, Redactor};
+use crate::wire::ToastAction;
+use serde_json::{json, Value};
+
+/// Action id of the Allow button.
+pub const ALLOW_ACTION: &str = "allow";
+/// Action id of the Deny button.
+pub const DENY_ACTION: &str = "deny";
+
+/// Reason handed back to Claude when the user clicks Deny.
+pub const DENY_MESSAGE: &str = "Denied from the Agent Toast notification";
+
+/// Outcome of a decide-mode toast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny,
+    /// No answer: let Claude Code show its own prompt.
+    Ask,
+}
+
+impl Decision {
+    /// Map the clicked action id (if any) to a decision.
+    pub fn from_action(action: Option<&str>) -> Self {
+        match action {
+            Some(ALLOW_ACTION) => Decision::Allow,
+            Some(DENY_ACTION) => Decision::Deny,
+            _ => Decision::Ask,
+        }
+    }
+
+    /// `PermissionRequest` hook output for this decision. `None` for
+    /// [`Decision::Ask`]: printing nothing keeps Claude Code's default flow.
+    pub fn hook_output(self) -> Option<Value> {
+        let decision = match self {
+            Decision::Allow => json!({ "behavior": "allow" }),
+            Decision::Deny => json!({ "behavior": "deny", "message": DENY_MESSAGE }),
+            Decision::Ask => return None,
+        };
+        Some(json!({
+            "hookSpecificOutput": {
+                "hookEventName": "PermissionRequest",
+                "decision": decision,
+            }
+        }))
+    }
+}
+
+/// The Allow / Deny buttons of a decide-mode toast.
+pub fn decision_actions(allow_label: &str, deny_label: &str) -> Vec<ToastAction> {
+    vec![
+        ToastAction {
+            id: ALLOW_ACTION.to_string(),
+            label: allow_label.to_string(),
+        },
+        ToastAction {
+            id: DENY_ACTION.to_string(),
+            label: deny_label.to_string(),
+        },
+    ]
+}
+
+/// Toast body for a permission request: the tool's own description when it
+/// has one, else its [`summarize_tool`] summary, else `tool_name`, else the
+/// static `fallback`. Secrets in the tool's input are masked by `redactor`.
+pub fn request_message(
+    hook: &Value,
+    fallback: Option<&str>,
+    redactor: &Redactor,
+) -> Option<String> {
+    extract_tool_description(hook)
+        .or_else(|| summarize_tool(hook))
+        .or_else(|| {
+            hook.get("tool_name")
+                .and_then(|t| t.as_str())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+        })
+        .map(|s| normalize_message(&s, redactor))
+        .or_else(|| fallback.map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_action_maps_known_ids() {
+        assert_eq!(Decision::from_action(Some("allow")), Decision::Allow);
+        assert_eq!(Decision::from_action(Some("deny")), Decision::Deny);
+        assert_eq!(Decision::from_action(Some("other")), Decision::Ask);
+        assert_eq!(Decision::from_action(None), Decision::Ask);
+    }
+
+    #[test]
+    fn allow_output_matches_hook_schema() {
+        let out = Decision::Allow.hook_output().unwrap();
+        assert_eq!(
+            out,
+            json!({
+                "hookSpecificOutput": {
+                    "hookEventName": "PermissionRequest",
+                    "decision": { "behavior": "allow" }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn deny_output_carries_message() {
+        let out = Decision::Deny.hook_output().unwrap();
+        let decision = &out["hookSpecificOutput"]["decision"];
+        assert_eq!(decision["behavior"], "deny");
+        assert_eq!(decision["message"], DENY_MESSAGE);
+    }
+
+    #[test]
+    fn ask_prints_nothing() {
+        assert!(Decision::Ask.hook_output().is_none());
+    }
+
+    #[test]
+    fn decision_actions_use_fixed_ids() {
+        let actions = decision_actions("허용", "거부");
+        assert_eq!(actions[0].id, ALLOW_ACTION);
+        assert_eq!(actions[0].label, "허용");
+        assert_eq!(actions[1].id, DENY_ACTION);
+    }
+
+    #[test]
+    fn request_message_prefers_tool_description() {
+        let hook = json!({"tool_name": "Bash", "tool_input": {"description": "List files"}});
+        assert_eq!(
+            request_message(&hook, Some("fb"), &Redactor::default()).as_deref(),
+            Some("List files")
+        );
+    }
+
+    #[test]
+    fn request_message_summarizes_tools_without_description() {
+        let hook = json!({
+            "tool_name": "Edit",
+            "tool_input": {"file_path": "a.rs", "old_string": "x", "new_string": "y\nz"}
+        });
+        assert_eq!(
+            request_message(&hook, Some("fb"), &Redactor::default()).as_deref(),
+            Some("Edit a.rs (+2/−1)")
+        );
+    }
+
+    #[test]
+    fn request_message_falls_back_to_tool_name_then_fallback() {
+        let hook = json!({"tool_name": "TodoWrite", "tool_input": {"todos": []}});
+        assert_eq!(
+            request_message(&hook, Some("fb"), &Redactor::default()).as_deref(),
+            Some("TodoWrite")
+        );
+        assert_eq!(
+            request_message(&json!({}), Some("fb"), &Redactor::default()).as_deref(),
+            Some("fb")
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! JSON Schemas (draft 2020-12) for the wire format and the `agent_toast`
+//! settings block, for non-Rust senders and editors validating a hand-edited
+//! `settings.json`. Printed by `agent-toast-send schema`.
+//!
+//! Hand-written rather than derived so descriptions can speak to script
+//! authors; the tests here and in the desktop's `setup.rs` fail when a field
+//! is added without being described.
+
+use crate::wire::{MAX_ACTIONS, MAX_EXTRA_FIELDS, MAX_EXTRA_VALUE_CHARS, SCHEMA_VERSION};
+use serde_json::{json, Value};
+
+/// `$schema` dialect of every schema in this module.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+fn string_or_null(description: &str) -> Value {
+    json!({ "type": ["string", "null"], "description": description })
+}
+
+fn enum_of(values: &[&str], default: &str, description: &str) -> Value {
+    json!({ "type": "string", "enum": values, "default": default, "description": description })
+}
+
+fn boolean(default: bool, description: &str) -> Value {
+    json!({ "type": "boolean", "default": default, "description": description })
+}
+
+/// Schema for one `POST /notify` body (and one item of `/notify/batch`).
+///
+/// Unknown properties are allowed: receivers ignore fields from newer
+/// senders.
+pub fn notify_request() -> Value {
+    json!({
+        "$schema": DIALECT,
+        "title": "NotifyRequest",
+        "description": format!(
+            "Notification sent to the Agent Toast desktop (wire schema v{SCHEMA_VERSION})."
+        ),
+        "type": "object",
+        "required": ["pid", "event"],
+        "properties": {
+            "pid": {
+                "type": "integer", "minimum": 0, "maximum": u32::MAX,
+                "description": "Sender process id; 0 for remote senders.",
+            },
+            "event": {
+                "type": "string",
+                "examples": ["task_complete", "user_input_required", "error"],
+                "description": "Event kind; selects the toast's icon, label and color.",
+            },
+            "message": string_or_null("Toast body."),
+            "title_hint": string_or_null("Title override, usually the project directory name."),
+            "process_tree": {
+                "type": ["array", "null"],
+                "items": { "type": "integer", "minimum": 0, "maximum": u32::MAX },
+                "description": "Pre-resolved ancestor pids, used to find the source window.",
+            },
+            "source": {
+                "type": "string", "default": "claude", "examples": ["claude", "codex"],
+                "description": "Agent that produced the notification.",
+            },
+            "hostname": string_or_null(
+                "Sender host label; set for remote notifications, shown as a badge."
+            ),
+            "schema_version": {
+                "type": "integer", "minimum": 0, "default": 0,
+                "description": "Wire schema version of the sender; 0 when unversioned.",
+            },
+            "dedupe_key": string_or_null(
+                "Idempotency key: repeats from the same host within the dedupe window are dropped. `id` is accepted as an alias."
+            ),
+            "actions": {
+                "type": "array",
+                "maxItems": MAX_ACTIONS,
+                "default": [],
+                "items": { "$ref": "#/$defs/ToastAction" },
+                "description": "Toast buttons. When present the reply carries a ticket to poll at /actions/<ticket>.",
+            },
+            "priority": {
+                "type": "string",
+                "enum": ["low", "normal", "high", "critical"],
+                "default": "normal",
+                "description": "Urgency: sound, stickiness, rate limiting and focus skip. Unknown values read as normal.",
+            },
+            "session_id": string_or_null("Agent session id from the hook payload."),
+            "cwd": string_or_null("Working directory of the agent session."),
+            "transcript_path": string_or_null("Path of the session transcript (JSONL)."),
+            "hook_event_name": string_or_null("Hook event that fired, e.g. `Stop`."),
+            "sent_at": {
+                "type": ["integer", "null"], "minimum": 0,
+                "description": "Send time in Unix milliseconds.",
+            },
+            "extra": {
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+                "default": {},
+                "description": format!(
+                    "Labeled metadata shown as key/value rows under the message; the first {MAX_EXTRA_FIELDS} keys in sorted order, values cut at {MAX_EXTRA_VALUE_CHARS} chars."
+                ),
+            },
+        },
+        "additionalProperties": true,
+        "$defs": {
+            "ToastAction": {
+                "type": "object",
+                "required": ["id", "label"],
+                "properties": {
+                    "id": { "type": "string", "description": "Reported back when clicked." },
+                    "label": { "type": "string", "description": "Button text." },
+                },
+            },
+        },
+    })
+}
+
+/// Schema for the `agent_toast` block of `settings.json`.
+///
+/// Unknown keys are allowed: the desktop preserves keys it doesn't manage.
+pub fn settings_block() -> Value {
+    json!({
+        "type": "object",
+        "description": "Agent Toast desktop settings. Written by the settings window; safe to edit by hand.",
+        "properties": {
+            "title_display_mode": enum_of(
+                &["project", "window"], "project",
+                "Toast title: project directory name or source window title."
+            ),
+            "auto_close_on_focus": boolean(true, "Close toasts when their source window gains focus."),
+            "auto_dismiss_seconds": {
+                "type": "integer", "minimum": 0, "default": 0,
+                "description": "Close toasts after this many seconds; 0 keeps them until closed.",
+            },
+            "dedupe_window_seconds": {
+                "type": "integer", "minimum": 0, "default": 30,
+                "description": "How long a dedupe_key suppresses repeats; 0 disables dedupe.",
+            },
+            "notification_position": enum_of(
+                &["top_left", "top_right", "bottom_left", "bottom_right"], "bottom_right",
+                "Screen corner toasts stack from."
+            ),
+            "notification_sound": boolean(true, "Play a sound when a toast appears."),
+            "notification_sound_file": string_or_null("Custom sound file; null for the system sound."),
+            "notification_monitor": {
+                "type": "string", "default": "primary",
+                "description": "`primary` or a monitor index (`\"0\"`, `\"1\"`, ...).",
+            },
+            "locale": enum_of(&["ko", "en"], "en", "UI language; defaults to the system language."),
+            "auto_start": boolean(true, "Start the desktop app at login."),
+            "codex_enabled": boolean(false, "Register the Codex CLI notify hook."),
+            "http_enabled": boolean(false, "Accept remote notifications over HTTP."),
+            "http_port": {
+                "type": "integer", "minimum": 1, "maximum": 65535, "default": 38787,
+                "description": "HTTP listen port.",
+            },
+            "show_hostname": boolean(true, "Show the sender host badge on remote toasts."),
+            "dynamic_message_enabled": boolean(true, "Derive toast bodies from the hook payload."),
+            "permission_decide_enabled": boolean(
+                false, "Answer PermissionRequest hooks from Allow/Deny buttons on the toast."
+            ),
+            "hook_timeout_seconds": {
+                "type": "integer", "minimum": 0, "default": 10,
+                "description": "Hook timeout in seconds, so an unresponsive desktop can't stall Claude Code; 0 keeps Claude's default. Decide hooks get their wait on top.",
+            },
+            "hook_async": boolean(false, "Run notification hooks in the background (not decide hooks)."),
+            "theme": enum_of(&["system", "light", "dark"], "system", "Settings window theme."),
+            "global_stats_enabled": {
+                "type": "boolean",
+                "description": "Share anonymous usage counters.",
+            },
+            "toast_bar": enum_of(&["left", "none"], "none", "Accent bar on the toast's edge."),
+            "toast_border": enum_of(&["subtle", "accent"], "subtle", "Toast border style."),
+            "toast_effects": {
+                "type": "array",
+                "items": { "type": "string", "enum": ["ring", "breathe", "pulse", "shimmer"] },
+                "uniqueItems": true,
+                "default": [],
+                "description": "Animated effects.",
+            },
+            "toast_body": enum_of(&["glow", "tint", "flat"], "glow", "Toast background style."),
+            "toast_density": enum_of(&["comfortable", "compact"], "comfortable", "Toast padding."),
+            "toast_font_sans": { "type": "string", "default": "", "description": "UI font family; empty for the default." },
+            "toast_font_mono": { "type": "string", "default": "", "description": "Monospace font family; empty for the default." },
+            "hook_scope": enum_of(&["user", "project", "local"], "user", "settings.json the desktop registers hooks in: ~/.claude (user), or project_dir's .claude/settings.json (project) or .claude/settings.local.json (local)."),
+            "project_dir": { "type": "string", "default": "", "description": "Project root for the project and local hook scopes." },
+            "redact_patterns": {
+                "type": "array",
+                "items": { "type": "string", "format": "regex" },
+                "default": [],
+                "description": "Extra regexes whose matches are replaced with [REDACTED] in toast bodies, on top of the built-in secret detectors.",
+            },
+            "version": {
+                "type": "string",
+                "description": "App version that last wrote this block (informational).",
+            },
+        },
+        "additionalProperties": true,
+    })
+}
+
+/// Schema for `settings.json` describing only the `agent_toast` block; every
+/// other key (hooks, permissions, ...) is left to Claude Code's own schema.
+pub fn settings_file() -> Value {
+    json!({
+        "$schema": DIALECT,
+        "title": "Agent Toast settings",
+        "type": "object",
+        "properties": {
+            "agent_toast": { "$ref": "#/$defs/agent_toast" },
+        },
+        "additionalProperties": true,
+        "$defs": { "agent_toast": settings_block() },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NotifyRequest;
+
+    #[test]
+    fn every_serialized_field_is_described() {
+        let req: NotifyRequest = serde_json::from_value(json!({
+            "pid": 1, "event": "task_complete", "actions": [{"id": "a", "label": "A"}],
+            "session_id": "s", "cwd": "/p", "transcript_path": "/t", "hook_event_name": "Stop",
+            "sent_at": 1, "extra": {"branch": "main"},
+        }))
+        .unwrap();
+        let schema = notify_request();
+        let described = schema["properties"].as_object().unwrap();
+        for key in serde_json::to_value(&req)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .keys()
+        {
+            assert!(described.contains_key(key), "`{key}` missing from schema");
+        }
+        for key in described.keys() {
+            assert!(
+                serde_json::to_value(&req).unwrap().get(key).is_some(),
+                "schema describes `{key}` which NotifyRequest doesn't serialize"
+            );
+        }
+    }
+
+    #[test]
+    fn required_fields_match_deserializer() {
+        assert!(serde_json::from_value::<NotifyRequest>(json!({"pid": 1})).is_err());
+        assert!(serde_json::from_value::<NotifyRequest>(json!({"event": "x"})).is_err());
+        assert_eq!(notify_request()["required"], json!(["pid", "event"]));
+    }
+
+    #[test]
+    fn action_limit_follows_wire() {
+        assert_eq!(
+            notify_request()["properties"]["actions"]["maxItems"],
+            MAX_ACTIONS
+        );
+    }
+
+    #[test]
+    fn settings_file_references_block() {
+        let file = settings_file();
+        assert_eq!(file["$schema"], DIALECT);
+        assert_eq!(
+            file["properties"]["agent_toast"]["$ref"],
+            "#/$defs/agent_toast"
+        );
+        assert_eq!(file["$defs"]["agent_toast"], settings_block());
+    }
+}
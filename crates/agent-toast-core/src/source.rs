@@ -0,0 +1,477 @@
+//! Source adapters: one per coding agent, mapping that agent's raw hook
+//! payload to the parts of a toast.
+//!
+//! `NotifyRequest.source` names the agent; [`adapter`] looks up the matching
+//! [`SourceAdapter`] in [`ADAPTERS`]. Claude Code and Codex keep their
+//! dedicated CLI paths (`--dynamic` / `--template` on the hook's stdin, and
+//! `--codex` on the notify argument) and are registered here so the registry
+//! is the one list of known sources. The others are selected with `--source`:
+//!
+//! | source     | payload                                              |
+//! |------------|------------------------------------------------------|
+//! | `gemini`   | Gemini CLI hook stdin (`hook_event_name`, `prompt_response`, ...) |
+//! | `cursor`   | Cursor `hooks.json` stdin (`hook_event_name`, `workspace_roots`, ...) |
+//! | `windsurf` | Windsurf Cascade hook stdin (`agent_action_name`, `tool_info`, ...) |
+//! | `aider`    | none: `--notifications-command` runs without input   |
+
+use crate::codex::CodexNotification;
+use crate::dynamic::{normalize_message, resolve_message, summarize_tool, Redactor};
+use crate::wire::HookContext;
+use serde_json::Value;
+
+/// Stats bucket for sources no adapter claims.
+pub const OTHER: &str = "other";
+
+/// What an adapter extracted from one payload. `None` fields are left to the
+/// command line (`--event`, `--message`, `--title`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Adapted {
+    pub event: Option<String>,
+    /// Already normalized and redacted ([`normalize_message`]).
+    pub message: Option<String>,
+    pub title_hint: Option<String>,
+    /// Session id, cwd and hook event name. Not stamped.
+    pub context: HookContext,
+}
+
+/// Maps one agent's raw payload to event, message, title and session.
+pub trait SourceAdapter: Sync {
+    /// `source` value that selects this adapter.
+    fn source(&self) -> &'static str;
+
+    /// Whether the agent hands the CLI a payload on stdin. When `false` the
+    /// CLI doesn't read stdin and [`adapt`](Self::adapt) gets `Value::Null`.
+    fn reads_payload(&self) -> bool {
+        true
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted;
+}
+
+/// Every registered adapter.
+pub static ADAPTERS: &[&dyn SourceAdapter] =
+    &[&Claude, &Codex, &Gemini, &Cursor, &Windsurf, &Aider];
+
+/// The adapter registered for `source` (case-insensitive).
+pub fn adapter(source: &str) -> Option<&'static dyn SourceAdapter> {
+    let source = source.trim();
+    ADAPTERS
+        .iter()
+        .copied()
+        .find(|a| a.source().eq_ignore_ascii_case(source))
+}
+
+/// `source` as a stats key: the registered name, or [`OTHER`] so free-form
+/// values don't grow the stats file without bound. App-internal sources
+/// (e.g. `updater`) pass through untouched.
+pub fn stats_key(source: &str) -> &str {
+    match adapter(source) {
+        Some(a) => a.source(),
+        None if source == "updater" => source,
+        None => OTHER,
+    }
+}
+
+fn str_at<'a>(v: &'a Value, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(v, |v, key| v.get(key))?
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn owned(s: Option<&str>) -> Option<String> {
+    s.map(str::to_string)
+}
+
+fn message(text: Option<&str>, redactor: &Redactor) -> Option<String> {
+    text.map(|t| normalize_message(t, redactor))
+        .filter(|m| !m.is_empty())
+}
+
+/// Context + title from a session id, cwd and event name.
+fn with_context(
+    mut adapted: Adapted,
+    session_id: Option<&str>,
+    cwd: Option<&str>,
+    event_name: Option<&str>,
+) -> Adapted {
+    adapted.context = HookContext {
+        session_id: owned(session_id),
+        cwd: owned(cwd),
+        hook_event_name: owned(event_name),
+        ..Default::default()
+    };
+    adapted.title_hint = adapted.context.cwd_name();
+    adapted
+}
+
+/// "<tool> <main argument>" for agents whose tool names
+/// [`summarize_tool`] doesn't know.
+fn generic_tool_summary(name: &str, input: &Value) -> String {
+    const ARG_KEYS: &[&str] = &[
+        "command",
+        "file_path",
+        "absolute_path",
+        "path",
+        "pattern",
+        "query",
+        "url",
+    ];
+    match ARG_KEYS.iter().find_map(|k| str_at(input, &[k])) {
+        Some(arg) => format!("{name} {arg}"),
+        None => name.to_string(),
+    }
+}
+
+const TASK_COMPLETE: &str = "task_complete";
+const USER_INPUT_REQUIRED: &str = "user_input_required";
+const ERROR: &str = "error";
+
+/// Claude Code hook stdin. The event comes from
+/// [`event_from_hook`](crate::hook_config::event_from_hook).
+pub struct Claude;
+
+impl SourceAdapter for Claude {
+    fn source(&self) -> &'static str {
+        "claude"
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted {
+        let context = HookContext::from_hook(payload);
+        Adapted {
+            event: crate::hook_config::event_from_hook(payload).map(str::to_string),
+            message: resolve_message(payload, None, redactor),
+            title_hint: context.cwd_name(),
+            context,
+        }
+    }
+}
+
+/// Codex CLI notify JSON ([`crate::codex`]).
+pub struct Codex;
+
+impl SourceAdapter for Codex {
+    fn source(&self) -> &'static str {
+        crate::codex::SOURCE
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted {
+        let n: CodexNotification = serde_json::from_value(payload.clone()).unwrap_or_default();
+        let context = n.context();
+        Adapted {
+            event: Some(n.event()),
+            message: n.message(redactor),
+            title_hint: context.cwd_name(),
+            context,
+        }
+    }
+}
+
+/// Gemini CLI hooks: Claude-like stdin with `AfterAgent` carrying the
+/// response in `prompt_response` and `Notification` carrying `message`.
+pub struct Gemini;
+
+impl SourceAdapter for Gemini {
+    fn source(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted {
+        let name = str_at(payload, &["hook_event_name"]);
+        let tool = str_at(payload, &["tool_name"]).map(|t| {
+            summarize_tool(payload).unwrap_or_else(|| {
+                generic_tool_summary(t, payload.get("tool_input").unwrap_or(&Value::Null))
+            })
+        });
+        let failed = payload
+            .get("tool_response")
+            .and_then(|r| r.get("error"))
+            .is_some_and(|e| !e.is_null());
+        let (event, text) = match name {
+            Some("AfterAgent") => (
+                Some(TASK_COMPLETE),
+                owned(str_at(payload, &["prompt_response"])),
+            ),
+            Some("Notification") => (
+                Some(USER_INPUT_REQUIRED),
+                owned(str_at(payload, &["message"])),
+            ),
+            Some("AfterTool") if failed => (Some(ERROR), tool),
+            Some("AfterTool") => (Some(TASK_COMPLETE), tool),
+            Some("SessionEnd") | Some("PreCompress") => (Some(TASK_COMPLETE), None),
+            _ => (None, tool),
+        };
+        let adapted = Adapted {
+            event: event.map(str::to_string),
+            message: message(text.as_deref(), redactor),
+            ..Default::default()
+        };
+        with_context(
+            adapted,
+            str_at(payload, &["session_id"]),
+            str_at(payload, &["cwd"]),
+            name,
+        )
+    }
+}
+
+/// Cursor hooks (`~/.cursor/hooks.json`): camelCase `hook_event_name`,
+/// `conversation_id` as the session and `workspace_roots` for the project.
+pub struct Cursor;
+
+impl SourceAdapter for Cursor {
+    fn source(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted {
+        let name = str_at(payload, &["hook_event_name"]);
+        let (event, text) = match name {
+            Some("stop") => {
+                let event = match str_at(payload, &["status"]) {
+                    Some("error") => ERROR,
+                    _ => TASK_COMPLETE,
+                };
+                (Some(event), None)
+            }
+            Some("beforeShellExecution") => (
+                Some(USER_INPUT_REQUIRED),
+                owned(str_at(payload, &["command"])),
+            ),
+            Some("beforeMCPExecution") => {
+                let tool = str_at(payload, &["tool_name"]);
+                let text = match (str_at(payload, &["server"]), tool) {
+                    (Some(server), Some(tool)) => Some(format!("{server}: {tool}")),
+                    (_, tool) => owned(tool),
+                };
+                (Some(USER_INPUT_REQUIRED), text)
+            }
+            Some("afterFileEdit") => (Some(TASK_COMPLETE), owned(str_at(payload, &["file_path"]))),
+            Some("beforeSubmitPrompt") => (None, owned(str_at(payload, &["prompt"]))),
+            _ => (None, None),
+        };
+        let cwd = str_at(payload, &["cwd"]).or_else(|| {
+            payload
+                .get("workspace_roots")
+                .and_then(|r| r.get(0))
+                .and_then(|r| r.as_str())
+        });
+        let adapted = Adapted {
+            event: event.map(str::to_string),
+            message: message(text.as_deref(), redactor),
+            ..Default::default()
+        };
+        with_context(adapted, str_at(payload, &["conversation_id"]), cwd, name)
+    }
+}
+
+/// Windsurf Cascade hooks: snake_case `agent_action_name` with details
+/// under `tool_info` and `trajectory_id` as the session.
+pub struct Windsurf;
+
+impl SourceAdapter for Windsurf {
+    fn source(&self) -> &'static str {
+        "windsurf"
+    }
+
+    fn adapt(&self, payload: &Value, redactor: &Redactor) -> Adapted {
+        let name = str_at(payload, &["agent_action_name"]);
+        let info = |key: &str| str_at(payload, &["tool_info", key]);
+        let (event, text) = match name {
+            Some("post_cascade_response") => (Some(TASK_COMPLETE), owned(info("response"))),
+            Some("pre_run_command") => (Some(USER_INPUT_REQUIRED), owned(info("command_line"))),
+            Some("post_run_command") => (Some(TASK_COMPLETE), owned(info("command_line"))),
+            Some("pre_mcp_tool_use") => {
+                let text = match (info("mcp_server_name"), info("mcp_tool_name")) {
+                    (Some(server), Some(tool)) => Some(format!("{server}: {tool}")),
+                    (_, tool) => owned(tool),
+                };
+                (Some(USER_INPUT_REQUIRED), text)
+            }
+            Some("post_write_code") => (Some(TASK_COMPLETE), owned(info("file_path"))),
+            Some("pre_user_prompt") => (None, owned(info("user_prompt"))),
+            _ => (None, None),
+        };
+        let adapted = Adapted {
+            event: event.map(str::to_string),
+            message: message(text.as_deref(), redactor),
+            ..Default::default()
+        };
+        with_context(
+            adapted,
+            str_at(payload, &["trajectory_id"]),
+            info("cwd"),
+            name,
+        )
+    }
+}
+
+/// Aider's `--notifications-command`, run when Aider waits for input. It
+/// passes nothing, so the event is fixed and the body comes from `--message`.
+pub struct Aider;
+
+impl SourceAdapter for Aider {
+    fn source(&self) -> &'static str {
+        "aider"
+    }
+
+    fn reads_payload(&self) -> bool {
+        false
+    }
+
+    fn adapt(&self, _payload: &Value, _redactor: &Redactor) -> Adapted {
+        Adapted {
+            event: Some(USER_INPUT_REQUIRED.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn adapt(source: &str, payload: Value) -> Adapted {
+        adapter(source)
+            .unwrap()
+            .adapt(&payload, &Redactor::default())
+    }
+
+    #[test]
+    fn registry_lookup() {
+        for a in ADAPTERS {
+            assert_eq!(adapter(a.source()).unwrap().source(), a.source());
+        }
+        assert_eq!(adapter(" Gemini ").unwrap().source(), "gemini");
+        assert!(adapter("copilot").is_none());
+    }
+
+    #[test]
+    fn stats_key_folds_unknown_sources() {
+        assert_eq!(stats_key("cursor"), "cursor");
+        assert_eq!(stats_key("CLAUDE"), "claude");
+        assert_eq!(stats_key("updater"), "updater");
+        assert_eq!(stats_key("my-ci-bot"), OTHER);
+    }
+
+    #[test]
+    fn gemini_after_agent() {
+        let a = adapt(
+            "gemini",
+            json!({
+                "session_id": "g-1", "cwd": "/w/app", "hook_event_name": "AfterAgent",
+                "prompt": "fix it", "prompt_response": "Fixed the **parser**."
+            }),
+        );
+        assert_eq!(a.event.as_deref(), Some("task_complete"));
+        assert_eq!(a.message.as_deref(), Some("Fixed the parser."));
+        assert_eq!(a.title_hint.as_deref(), Some("app"));
+        assert_eq!(a.context.session_id.as_deref(), Some("g-1"));
+        assert_eq!(a.context.hook_event_name.as_deref(), Some("AfterAgent"));
+    }
+
+    #[test]
+    fn gemini_notification_and_tools() {
+        let a = adapt(
+            "gemini",
+            json!({"hook_event_name": "Notification", "notification_type": "ToolPermission",
+                   "message": "Allow run_shell_command?"}),
+        );
+        assert_eq!(a.event.as_deref(), Some("user_input_required"));
+        assert_eq!(a.message.as_deref(), Some("Allow run_shell_command?"));
+
+        let a = adapt(
+            "gemini",
+            json!({"hook_event_name": "AfterTool", "tool_name": "run_shell_command",
+                   "tool_input": {"command": "npm test"}, "tool_response": {"error": "exit 1"}}),
+        );
+        assert_eq!(a.event.as_deref(), Some("error"));
+        assert_eq!(a.message.as_deref(), Some("run_shell_command npm test"));
+    }
+
+    #[test]
+    fn cursor_events() {
+        let a = adapt(
+            "cursor",
+            json!({"hook_event_name": "stop", "status": "completed",
+                   "conversation_id": "c-1", "workspace_roots": ["/Users/me/site"]}),
+        );
+        assert_eq!(a.event.as_deref(), Some("task_complete"));
+        assert_eq!(a.message, None);
+        assert_eq!(a.title_hint.as_deref(), Some("site"));
+        assert_eq!(a.context.session_id.as_deref(), Some("c-1"));
+
+        let a = adapt(
+            "cursor",
+            json!({"hook_event_name": "beforeShellExecution", "command": "rm -rf dist", "cwd": "/x/y"}),
+        );
+        assert_eq!(a.event.as_deref(), Some("user_input_required"));
+        assert_eq!(a.message.as_deref(), Some("rm -rf dist"));
+        assert_eq!(a.title_hint.as_deref(), Some("y"));
+
+        let a = adapt(
+            "cursor",
+            json!({"hook_event_name": "stop", "status": "error"}),
+        );
+        assert_eq!(a.event.as_deref(), Some("error"));
+    }
+
+    #[test]
+    fn windsurf_events() {
+        let a = adapt(
+            "windsurf",
+            json!({"agent_action_name": "post_cascade_response", "trajectory_id": "t-1",
+                   "tool_info": {"response": "All done."}}),
+        );
+        assert_eq!(a.event.as_deref(), Some("task_complete"));
+        assert_eq!(a.message.as_deref(), Some("All done."));
+        assert_eq!(a.context.session_id.as_deref(), Some("t-1"));
+
+        let a = adapt(
+            "windsurf",
+            json!({"agent_action_name": "pre_run_command",
+                   "tool_info": {"command_line": "cargo publish", "cwd": "/src/crate"}}),
+        );
+        assert_eq!(a.event.as_deref(), Some("user_input_required"));
+        assert_eq!(a.message.as_deref(), Some("cargo publish"));
+        assert_eq!(a.title_hint.as_deref(), Some("crate"));
+    }
+
+    #[test]
+    fn aider_needs_no_payload() {
+        let aider = adapter("aider").unwrap();
+        assert!(!aider.reads_payload());
+        let a = aider.adapt(&Value::Null, &Redactor::default());
+        assert_eq!(a.event.as_deref(), Some("user_input_required"));
+        assert_eq!(a.message, None);
+    }
+
+    #[test]
+    fn claude_and_codex_adapters_match_their_cli_paths() {
+        let a = adapt(
+            "claude",
+            json!({"session_id": "s", "cwd": "/p/q", "last_assistant_message": "done"}),
+        );
+        assert_eq!(a.event, None);
+        assert_eq!(a.message.as_deref(), Some("done"));
+        assert_eq!(a.title_hint.as_deref(), Some("q"));
+        let a = adapt("claude", json!({"hook_event_name": "PostToolUseFailure"}));
+        assert_eq!(a.event.as_deref(), Some("error"));
+
+        let a = adapt(
+            "codex",
+            json!({"type": "agent-turn-complete", "turn-id": "1", "last-assistant-message": "ok"}),
+        );
+        assert_eq!(a.event.as_deref(), Some("agent_turn_complete"));
+        assert_eq!(a.message.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn unknown_events_leave_event_to_the_cli() {
+        let a = adapt("cursor", json!({"hook_event_name": "beforeReadFile"}));
+        assert_eq!(a.event, None);
+        assert_eq!(adapt("gemini", json!({})).event, None);
+    }
+}
@@ -0,0 +1,153 @@
+//! `--template` rendering against a hook's stdin JSON.
+//!
+//! A template is plain text with `{placeholder}`s. A placeholder is a dotted
+//! path into the payload — `{cwd}`, `{tool_input.command}`,
+//! `{tool_input.edits.0.file_path}` (array index) — optionally followed by
+//! `|N` to cap it at N grapheme clusters: `{last_assistant_message|80}`.
+//! Missing or null values render as nothing; numbers and booleans render as
+//! written, objects and arrays as compact JSON. `{{` and `}}` are literal
+//! braces, and a `{` without a closing `}` is kept as-is.
+
+use crate::dynamic::truncate_graphemes;
+use serde_json::Value;
+
+/// Render `template` against `hook`.
+pub fn render(template: &str, hook: &Value) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if let Some(after) = tail.strip_prefix('}') {
+            out.push('}');
+            rest = after;
+            continue;
+        }
+        match tail.find('}') {
+            Some(end) => {
+                out.push_str(&placeholder(&tail[1..end], hook));
+                rest = &tail[end + 1..];
+            }
+            None => {
+                out.push_str(tail);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Value of one `path|N` placeholder body.
+fn placeholder(spec: &str, hook: &Value) -> String {
+    let mut parts = spec.split('|').map(str::trim);
+    let path = parts.next().unwrap_or_default();
+    let mut text = lookup(hook, path).map(display).unwrap_or_default();
+    for filter in parts {
+        match filter.parse::<usize>() {
+            Ok(max) => text = truncate_graphemes(&text, max),
+            Err(_) => log::debug!("unknown template filter {filter:?} in {{{spec}}}"),
+        }
+    }
+    text
+}
+
+/// Resolve a dotted path; numeric segments index into arrays.
+pub fn lookup<'a>(hook: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return None;
+    }
+    path.split('.').try_fold(hook, |v, seg| match v {
+        Value::Array(items) => items.get(seg.parse::<usize>().ok()?),
+        _ => v.get(seg),
+    })
+}
+
+fn display(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hook() -> Value {
+        json!({
+            "session_id": "s-1",
+            "cwd": "/work/proj",
+            "tool_name": "Bash",
+            "tool_input": {
+                "command": "cargo test --workspace",
+                "timeout": 120000,
+                "edits": [{"file_path": "a.rs"}, {"file_path": "b.rs"}]
+            },
+            "last_assistant_message": "All 42 tests pass. Ready for review.",
+            "stop_hook_active": false,
+            "nothing": null
+        })
+    }
+
+    #[test]
+    fn renders_top_level_and_dotted_paths() {
+        assert_eq!(
+            render("{tool_name}: {tool_input.command} in {cwd}", &hook()),
+            "Bash: cargo test --workspace in /work/proj"
+        );
+        assert_eq!(render("{tool_input.edits.1.file_path}", &hook()), "b.rs");
+    }
+
+    #[test]
+    fn length_filter_caps_graphemes() {
+        assert_eq!(
+            render("{last_assistant_message|20}", &hook()),
+            "All 42 tests pass."
+        );
+        assert_eq!(render("{ session_id | 100 }", &hook()), "s-1");
+    }
+
+    #[test]
+    fn missing_and_null_render_empty() {
+        assert_eq!(
+            render("[{nope}][{nothing}][{tool_input.edits.9}]", &hook()),
+            "[][][]"
+        );
+        assert_eq!(render("{}", &hook()), "");
+    }
+
+    #[test]
+    fn non_strings_render_as_json() {
+        assert_eq!(
+            render("{tool_input.timeout} {stop_hook_active}", &hook()),
+            "120000 false"
+        );
+        assert_eq!(
+            render("{tool_input.edits.0}", &hook()),
+            r#"{"file_path":"a.rs"}"#
+        );
+    }
+
+    #[test]
+    fn braces_escape_and_unclosed() {
+        assert_eq!(
+            render("{{tool_name}} = {tool_name}", &hook()),
+            "{tool_name} = Bash"
+        );
+        assert_eq!(render("open { brace", &hook()), "open { brace");
+        assert_eq!(render("stray } brace", &hook()), "stray } brace");
+    }
+
+    #[test]
+    fn unknown_filter_is_ignored() {
+        assert_eq!(render("{tool_name|upper}", &hook()), "Bash");
+    }
+}
@@ -0,0 +1,200 @@
+//! Tail reader for Claude Code session transcripts (`transcript_path`, one
+//! JSON object per line).
+//!
+//! Hooks such as Notification (idle), SubagentStop, SessionEnd and PreCompact
+//! carry `transcript_path` but no `last_assistant_message`. The transcript can
+//! be many megabytes, so it is read backwards in chunks from the end and the
+//! scan stops at the first (i.e. latest) assistant entry with a text block.
+//! The last line may still be mid-write when the hook fires; a line that
+//! doesn't parse is skipped rather than treated as an error.
+
+use serde_json::Value;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// How far back from the end of the file to look before giving up.
+pub const MAX_SCAN_BYTES: u64 = 4 * 1024 * 1024;
+
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Text of the last assistant message in the transcript at `path`. `None`
+/// when the file can't be read or has no assistant text within
+/// [`MAX_SCAN_BYTES`] of its end.
+pub fn last_assistant_text(path: impl AsRef<Path>) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    last_assistant_text_from(file)
+}
+
+/// [`last_assistant_text`] over any seekable reader.
+pub fn last_assistant_text_from(reader: impl Read + Seek) -> Option<String> {
+    scan_backwards(reader, CHUNK_BYTES)
+}
+
+fn scan_backwards(mut r: impl Read + Seek, chunk_bytes: usize) -> Option<String> {
+    let end = r.seek(SeekFrom::End(0)).ok()?;
+    let floor = end.saturating_sub(MAX_SCAN_BYTES);
+    let mut pos = end;
+    // Start of the line that straddles the previous chunk boundary; it is only
+    // complete once the newline before it (or the start of the file) is read.
+    let mut carry: Vec<u8> = Vec::new();
+    while pos > floor {
+        let start = pos.saturating_sub(chunk_bytes as u64).max(floor);
+        let mut buf = vec![0u8; (pos - start) as usize];
+        r.seek(SeekFrom::Start(start)).ok()?;
+        r.read_exact(&mut buf).ok()?;
+        buf.extend_from_slice(&carry);
+        pos = start;
+
+        let complete_from = if pos == 0 {
+            0
+        } else {
+            match buf.iter().position(|&b| b == b'\n') {
+                Some(i) => i + 1,
+                None => {
+                    carry = buf;
+                    continue;
+                }
+            }
+        };
+        for line in buf[complete_from..].split(|&b| b == b'\n').rev() {
+            if let Some(text) = assistant_text(line) {
+                return Some(text);
+            }
+        }
+        buf.truncate(complete_from);
+        carry = buf;
+    }
+    None
+}
+
+/// Last non-empty text block of an assistant entry. `message.content` is
+/// either a string or an array of blocks (`text`, `tool_use`, `thinking`, ...).
+fn assistant_text(line: &[u8]) -> Option<String> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return None;
+    }
+    let entry: Value = serde_json::from_slice(line).ok()?;
+    if entry.get("type").and_then(|t| t.as_str()) != Some("assistant") {
+        return None;
+    }
+    let content = entry.get("message")?.get("content")?;
+    let text = match content {
+        Value::String(s) => s.as_str(),
+        Value::Array(blocks) => blocks.iter().rev().find_map(|b| {
+            (b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .then(|| b.get("text").and_then(|t| t.as_str()))
+                .flatten()
+                .filter(|t| !t.trim().is_empty())
+        })?,
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    fn line(v: Value) -> String {
+        format!("{v}\n")
+    }
+
+    fn assistant(text: &str) -> String {
+        line(json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [{"type": "text", "text": text}]}
+        }))
+    }
+
+    fn user(text: &str) -> String {
+        line(json!({"type": "user", "message": {"role": "user", "content": text}}))
+    }
+
+    #[test]
+    fn finds_latest_assistant_text() {
+        let t = [
+            assistant("first"),
+            user("q"),
+            assistant("second"),
+            user("q2"),
+        ]
+        .concat();
+        assert_eq!(
+            last_assistant_text_from(Cursor::new(t)).as_deref(),
+            Some("second")
+        );
+    }
+
+    #[test]
+    fn skips_tool_only_entries_and_picks_last_text_block() {
+        let tool_only = line(json!({
+            "type": "assistant",
+            "message": {"content": [{"type": "tool_use", "name": "Bash", "input": {}}]}
+        }));
+        let mixed = line(json!({
+            "type": "assistant",
+            "message": {"content": [
+                {"type": "text", "text": "Looking"},
+                {"type": "tool_use", "name": "Read"},
+                {"type": "text", "text": "Done."}
+            ]}
+        }));
+        let t = [mixed, tool_only].concat();
+        assert_eq!(
+            last_assistant_text_from(Cursor::new(t)).as_deref(),
+            Some("Done.")
+        );
+    }
+
+    #[test]
+    fn string_content_is_accepted() {
+        let t = line(json!({"type": "assistant", "message": {"content": "plain"}}));
+        assert_eq!(
+            last_assistant_text_from(Cursor::new(t)).as_deref(),
+            Some("plain")
+        );
+    }
+
+    #[test]
+    fn ignores_partially_flushed_last_line() {
+        let mut t = assistant("complete");
+        t.push_str(r#"{"type":"assistant","message":{"content":[{"type":"te"#);
+        assert_eq!(
+            last_assistant_text_from(Cursor::new(t)).as_deref(),
+            Some("complete")
+        );
+    }
+
+    #[test]
+    fn lines_spanning_chunks_are_reassembled() {
+        let long = "가".repeat(500);
+        let t = [assistant(&long), user("q")].concat();
+        for chunk in [1, 7, 64, 4096] {
+            assert_eq!(
+                scan_backwards(Cursor::new(t.clone()), chunk).as_deref(),
+                Some(long.as_str()),
+                "chunk={chunk}"
+            );
+        }
+    }
+
+    #[test]
+    fn crlf_and_blank_lines_are_tolerated() {
+        let t = format!("\r\n{}\r\n\r\n", assistant("hi").trim_end());
+        assert_eq!(
+            last_assistant_text_from(Cursor::new(t)).as_deref(),
+            Some("hi")
+        );
+    }
+
+    #[test]
+    fn no_assistant_text_is_none() {
+        assert_eq!(last_assistant_text_from(Cursor::new(user("q"))), None);
+        assert_eq!(last_assistant_text_from(Cursor::new(Vec::new())), None);
+        assert_eq!(last_assistant_text("/definitely/not/here.jsonl"), None);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Diagnostic version string reflecting the `agent-toast-core` crate version.
 ///
@@ -6,11 +7,318 @@ use serde::{Deserialize, Serialize};
 /// sender and desktop share compatible build versions when debugging
 /// cross-host notification issues.
 ///
-/// This is NOT a stable wire-schema version identifier. The wire format
-/// evolves via `#[serde(default)]` on new fields for forward/backward
-/// compatibility, without bumping this constant's meaning.
+/// This is NOT a stable wire-schema version identifier — see
+/// [`SCHEMA_VERSION`] for that.
 pub const WIRE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Integer version of the [`NotifyRequest`] schema.
+///
+/// Bumped whenever a field is added to the wire format. Senders stamp it on
+/// every request; the desktop advertises the highest version it understands
+/// via [`Capabilities`] so a newer sender can downgrade its payload instead of
+/// having new fields silently ignored. `0` means "sent by a build that predates
+/// schema versioning".
+pub const SCHEMA_VERSION: u32 = 6;
+
+/// Optional wire features understood by this build, advertised alongside
+/// [`SCHEMA_VERSION`]. Every field added after schema versioning gets a feature
+/// name here. Fields that change behavior also get an entry in
+/// [`NotifyRequest::required_features`] / [`NotifyRequest::downgrade_for`];
+/// advisory metadata (`hook_context`) is safe for an older receiver to ignore
+/// and is never stripped. `batch` names the `POST /notify/batch` endpoint
+/// rather than a field.
+pub const FEATURES: &[&str] = &[
+    "dedupe_key",
+    "actions",
+    "priority",
+    "hook_context",
+    "batch",
+    "extra",
+];
+
+/// What a desktop receiver understands, served at `GET /capabilities`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Receiver build version ([`WIRE_VERSION`]), for diagnostics only.
+    #[serde(default)]
+    pub app_version: Option<String>,
+}
+
+impl Capabilities {
+    /// Capabilities of this build.
+    pub fn current() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            features: FEATURES.iter().map(|f| f.to_string()).collect(),
+            app_version: Some(WIRE_VERSION.to_string()),
+        }
+    }
+
+    /// Assumed capabilities of a receiver that has no `/capabilities` endpoint:
+    /// it only understands the fields that existed before schema versioning.
+    pub fn legacy() -> Self {
+        Self {
+            schema_version: 0,
+            features: Vec::new(),
+            app_version: None,
+        }
+    }
+
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+}
+
+/// Upper bound on [`NotifyRequest::actions`] rendered on a toast; extra
+/// entries are ignored by the desktop.
+pub const MAX_ACTIONS: usize = 3;
+
+/// A button rendered on the toast. Clicking it settles the request's ticket
+/// with [`ActionResult::Clicked`] carrying `id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToastAction {
+    pub id: String,
+    pub label: String,
+}
+
+impl std::str::FromStr for ToastAction {
+    type Err = String;
+
+    /// Parse the CLI form `ID=LABEL`; a bare `ID` doubles as its own label.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (id, label) = s.split_once('=').unwrap_or((s, s));
+        let (id, label) = (id.trim(), label.trim());
+        if id.is_empty() || label.is_empty() {
+            return Err(format!("invalid action `{s}`: expected ID=LABEL"));
+        }
+        Ok(Self {
+            id: id.to_string(),
+            label: label.to_string(),
+        })
+    }
+}
+
+/// Upper bound on [`NotifyRequest::extra`] rows rendered on a toast; the
+/// desktop shows the first entries in key order.
+pub const MAX_EXTRA_FIELDS: usize = 8;
+
+/// Longest [`NotifyRequest::extra`] value rendered before truncation, in chars.
+pub const MAX_EXTRA_VALUE_CHARS: usize = 80;
+
+/// Parse the CLI form `KEY=VALUE` of an [`NotifyRequest::extra`] entry. The
+/// value may be empty; the key may not.
+pub fn parse_extra_field(s: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = s.split_once('=') else {
+        return Err(format!("invalid field `{s}`: expected KEY=VALUE"));
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(format!("invalid field `{s}`: empty key"));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Receiver's answer to a [`NotifyRequest`]: body of the HTTP `202` response
+/// and of the pipe response frame. `ticket` is only set when the request
+/// carried actions and a toast was actually shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifyReply {
+    #[serde(default)]
+    pub ticket: Option<String>,
+}
+
+/// Upper bound on the number of requests in one `POST /notify/batch`.
+pub const MAX_BATCH_ITEMS: usize = 100;
+
+/// Per-item result of `POST /notify/batch`; the response body is an array of
+/// these in request order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum NotifyOutcome {
+    /// Shown by the desktop. `ticket` is set when the toast has actions, as
+    /// in [`NotifyReply`].
+    Accepted {
+        #[serde(default)]
+        ticket: Option<String>,
+    },
+    /// Valid, but the desktop didn't show it.
+    Dropped { reason: DropReason },
+    /// Not a valid [`NotifyRequest`]; the rest of the batch is unaffected.
+    Rejected { error: String },
+}
+
+/// Why the desktop didn't show a valid request ([`NotifyOutcome::Dropped`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropReason {
+    /// Its `dedupe_key` was already shown within the duplicate window.
+    Duplicate,
+    /// The rate limiter was empty.
+    RateLimited,
+    /// The source window already had focus.
+    Focused,
+    /// Counted in the batch's summary toast instead of getting its own.
+    Coalesced,
+    /// The toast window couldn't be created.
+    Failed,
+}
+
+impl DropReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DropReason::Duplicate => "duplicate",
+            DropReason::RateLimited => "rate_limited",
+            DropReason::Focused => "focused",
+            DropReason::Coalesced => "coalesced",
+            DropReason::Failed => "failed",
+        }
+    }
+
+    /// `true` when sending the same request again later may show it.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::RateLimited | Self::Failed)
+    }
+}
+
+/// Pipe frame asking for the current [`ActionResult`] of a ticket (the HTTP
+/// equivalent is `GET /actions/<ticket>`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ActionPoll {
+    pub poll: String,
+}
+
+/// What happened to a toast that carried actions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ActionResult {
+    /// Still on screen.
+    Pending,
+    /// The user clicked the action with this id.
+    Clicked { action: String },
+    /// Closed without an action (close button, timeout, focus).
+    Dismissed,
+    /// The ticket was never issued or its result has expired.
+    Unknown,
+}
+
+impl ActionResult {
+    /// `true` once polling can stop.
+    pub fn is_settled(&self) -> bool {
+        !matches!(self, ActionResult::Pending)
+    }
+}
+
+/// How urgent a notification is. The desktop derives sound, stickiness, rate
+/// limiting and the focused-window skip from it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            "critical" => Ok(Priority::Critical),
+            _ => Err(format!(
+                "invalid priority `{s}`: expected low, normal, high or critical"
+            )),
+        }
+    }
+}
+
+/// Unknown priority names (from a newer sender) read as [`Priority::Normal`]
+/// instead of rejecting the whole request.
+fn lenient_priority<'de, D>(deserializer: D) -> Result<Priority, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    Ok(raw.and_then(|s| s.parse().ok()).unwrap_or_default())
+}
+
+/// Where a notification came from: fields copied from the Claude Code hook's
+/// stdin JSON, plus the sender's clock. Flattened into [`NotifyRequest`], so
+/// on the wire these are top-level keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookContext {
+    pub session_id: Option<String>,
+    /// Full working directory (unlike the basename in `title_hint`).
+    pub cwd: Option<String>,
+    pub transcript_path: Option<String>,
+    /// Hook event that fired, e.g. `Stop` or `PermissionRequest`.
+    pub hook_event_name: Option<String>,
+    /// Sender-side Unix time in milliseconds.
+    pub sent_at: Option<u64>,
+}
+
+impl HookContext {
+    /// Copy the context fields out of a hook payload. Missing, non-string or
+    /// blank values stay `None`; `sent_at` is left for [`Self::stamped`].
+    pub fn from_hook(hook: &serde_json::Value) -> Self {
+        let field = |key: &str| {
+            hook.get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Self {
+            session_id: field("session_id"),
+            cwd: field("cwd"),
+            transcript_path: field("transcript_path"),
+            hook_event_name: field("hook_event_name"),
+            sent_at: None,
+        }
+    }
+
+    /// Set `sent_at` to the current time.
+    pub fn stamped(mut self) -> Self {
+        self.sent_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_millis() as u64);
+        self
+    }
+
+    /// Last path component of `cwd`, usable as a `title_hint`.
+    pub fn cwd_name(&self) -> Option<String> {
+        let cwd = self.cwd.as_deref()?;
+        let name = cwd
+            .trim_end_matches(['/', '\\'])
+            .rsplit(['/', '\\'])
+            .next()?;
+        (!name.is_empty()).then(|| name.to_string())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotifyRequest {
     pub pid: u32,
@@ -33,6 +341,37 @@ pub struct NotifyRequest {
     ///   and render a host badge.
     #[serde(default)]
     pub hostname: Option<String>,
+
+    /// [`SCHEMA_VERSION`] of the sender. Missing (`0`) for senders that predate
+    /// schema versioning.
+    #[serde(default)]
+    pub schema_version: u32,
+
+    /// Idempotency key. The desktop drops a request whose key (scoped to
+    /// `hostname`) it has already shown within its dedupe window, so hook
+    /// retries and duplicate hook registrations produce a single toast.
+    /// `id` is accepted as an alias on input.
+    #[serde(default, alias = "id")]
+    pub dedupe_key: Option<String>,
+
+    /// Buttons rendered on the toast (at most [`MAX_ACTIONS`]). When non-empty
+    /// the receiver answers with a [`NotifyReply`] ticket the sender can poll
+    /// for the clicked action.
+    #[serde(default)]
+    pub actions: Vec<ToastAction>,
+
+    /// Urgency; [`Priority::Normal`] when missing.
+    #[serde(default, deserialize_with = "lenient_priority")]
+    pub priority: Priority,
+
+    /// Session/cwd/transcript metadata from the hook payload.
+    #[serde(flatten)]
+    pub context: HookContext,
+
+    /// Labeled metadata (branch, ticket, CI job, ...) rendered as compact
+    /// key/value rows under the message, at most [`MAX_EXTRA_FIELDS`].
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
 }
 
 fn default_source() -> String {
@@ -44,6 +383,65 @@ impl NotifyRequest {
         // Return the event key as-is; frontend translates via i18n
         &self.event
     }
+
+    /// Attach the hook payload's [`HookContext`], keeping the sender's
+    /// `sent_at`. A missing `title_hint` falls back to the cwd's folder name.
+    pub fn attach_hook_context(&mut self, hook: &serde_json::Value) {
+        self.context = HookContext {
+            sent_at: self.context.sent_at,
+            ..HookContext::from_hook(hook)
+        };
+        if self.title_hint.is_none() {
+            self.title_hint = self.context.cwd_name();
+        }
+    }
+
+    /// Optional features (see [`FEATURES`]) this request actually uses. A
+    /// sender only needs to query the receiver's [`Capabilities`] when this is
+    /// non-empty.
+    pub fn required_features(&self) -> Vec<&'static str> {
+        let mut features = Vec::new();
+        if self.dedupe_key.is_some() {
+            features.push("dedupe_key");
+        }
+        if !self.actions.is_empty() {
+            features.push("actions");
+        }
+        if self.priority != Priority::Normal {
+            features.push("priority");
+        }
+        if !self.extra.is_empty() {
+            features.push("extra");
+        }
+        features
+    }
+
+    /// Strip everything `caps` does not understand so the receiver never
+    /// silently drops part of the payload, and stamp the negotiated schema
+    /// version. Returns the names of the features that were removed so the
+    /// sender can warn about them.
+    pub fn downgrade_for(&mut self, caps: &Capabilities) -> Vec<&'static str> {
+        self.schema_version = self.schema_version.min(caps.schema_version);
+        let dropped: Vec<&'static str> = self
+            .required_features()
+            .into_iter()
+            .filter(|f| !caps.supports(f))
+            .collect();
+        for feature in &dropped {
+            self.clear_feature(feature);
+        }
+        dropped
+    }
+
+    fn clear_feature(&mut self, feature: &str) {
+        match feature {
+            "dedupe_key" => self.dedupe_key = None,
+            "actions" => self.actions.clear(),
+            "priority" => self.priority = Priority::Normal,
+            "extra" => self.extra.clear(),
+            _ => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -59,6 +457,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         }
     }
 
@@ -86,6 +490,12 @@ mod tests {
             process_tree: Some(vec![100, 200, 300]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let deserialized: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -147,6 +557,12 @@ mod tests {
             process_tree: Some(tree.clone()),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let deserialized: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -163,6 +579,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let deserialized: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -182,6 +604,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let deserialized: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -198,6 +626,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let deserialized: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -214,6 +648,12 @@ mod tests {
             process_tree: None,
             source: "updater".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         assert_eq!(req.pid, 0);
     }
@@ -228,6 +668,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         assert_eq!(req.event, "");
         assert_eq!(req.event_display(), "");
@@ -261,6 +707,12 @@ mod tests {
             process_tree: Some(vec![1, 2, 3]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let cloned = req.clone();
         assert_eq!(cloned.pid, req.pid);
@@ -288,6 +740,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: Some("prod-vps-01".into()),
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains(r#""hostname":"prod-vps-01""#));
@@ -305,6 +763,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: Some("회사-서버-01".into()),
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_str(&json).unwrap();
@@ -321,9 +785,370 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: Some(String::new()),
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: BTreeMap::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_str(&json).unwrap();
         assert_eq!(decoded.hostname.as_deref(), Some(""));
     }
+
+    #[test]
+    fn schema_version_missing_is_zero() {
+        let json = r#"{"pid":0,"event":"task_complete"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.schema_version, 0);
+    }
+
+    #[test]
+    fn schema_version_roundtrip() {
+        let req = make_request("task_complete");
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(&format!(r#""schema_version":{SCHEMA_VERSION}"#)));
+        let decoded: NotifyRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.schema_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn capabilities_current_advertises_schema_and_features() {
+        let caps = Capabilities::current();
+        assert_eq!(caps.schema_version, SCHEMA_VERSION);
+        assert_eq!(caps.features.len(), FEATURES.len());
+        assert_eq!(caps.app_version.as_deref(), Some(WIRE_VERSION));
+        for f in FEATURES {
+            assert!(caps.supports(f));
+        }
+    }
+
+    #[test]
+    fn capabilities_deserialize_tolerates_missing_optional_fields() {
+        let caps: Capabilities = serde_json::from_str(r#"{"schema_version":7}"#).unwrap();
+        assert_eq!(caps.schema_version, 7);
+        assert!(caps.features.is_empty());
+        assert!(caps.app_version.is_none());
+        assert!(!caps.supports("anything"));
+    }
+
+    #[test]
+    fn downgrade_for_legacy_receiver_stamps_version_zero() {
+        let mut req = make_request("task_complete");
+        let dropped = req.downgrade_for(&Capabilities::legacy());
+        assert!(dropped.is_empty());
+        assert_eq!(req.schema_version, 0);
+    }
+
+    #[test]
+    fn downgrade_for_newer_receiver_keeps_sender_version() {
+        let mut req = make_request("task_complete");
+        let caps = Capabilities {
+            schema_version: SCHEMA_VERSION + 5,
+            features: vec![],
+            app_version: None,
+        };
+        req.downgrade_for(&caps);
+        assert_eq!(req.schema_version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn dedupe_key_missing_is_none() {
+        let json = r#"{"pid":0,"event":"task_complete"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert!(req.dedupe_key.is_none());
+    }
+
+    #[test]
+    fn dedupe_key_accepts_id_alias() {
+        let json = r#"{"pid":0,"event":"task_complete","id":"turn-42"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.dedupe_key.as_deref(), Some("turn-42"));
+    }
+
+    #[test]
+    fn dedupe_key_is_a_required_feature_only_when_set() {
+        let mut req = make_request("task_complete");
+        assert!(req.required_features().is_empty());
+        req.dedupe_key = Some("k".into());
+        assert_eq!(req.required_features(), vec!["dedupe_key"]);
+    }
+
+    #[test]
+    fn downgrade_for_legacy_receiver_strips_dedupe_key() {
+        let mut req = make_request("task_complete");
+        req.dedupe_key = Some("k".into());
+        let dropped = req.downgrade_for(&Capabilities::legacy());
+        assert_eq!(dropped, vec!["dedupe_key"]);
+        assert!(req.dedupe_key.is_none());
+    }
+
+    #[test]
+    fn downgrade_for_current_receiver_keeps_dedupe_key() {
+        let mut req = make_request("task_complete");
+        req.dedupe_key = Some("k".into());
+        let dropped = req.downgrade_for(&Capabilities::current());
+        assert!(dropped.is_empty());
+        assert_eq!(req.dedupe_key.as_deref(), Some("k"));
+    }
+
+    #[test]
+    fn actions_missing_is_empty() {
+        let json = r#"{"pid":0,"event":"user_input_required"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert!(req.actions.is_empty());
+    }
+
+    #[test]
+    fn actions_roundtrip() {
+        let mut req = make_request("user_input_required");
+        req.actions = vec![ToastAction {
+            id: "allow".into(),
+            label: "Allow".into(),
+        }];
+        let json = serde_json::to_string(&req).unwrap();
+        let back: NotifyRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.actions, req.actions);
+        assert_eq!(back.required_features(), vec!["actions"]);
+    }
+
+    #[test]
+    fn downgrade_for_legacy_receiver_strips_actions() {
+        let mut req = make_request("user_input_required");
+        req.actions = vec![ToastAction {
+            id: "allow".into(),
+            label: "Allow".into(),
+        }];
+        let dropped = req.downgrade_for(&Capabilities::legacy());
+        assert_eq!(dropped, vec!["actions"]);
+        assert!(req.actions.is_empty());
+    }
+
+    #[test]
+    fn action_result_is_tagged_by_status() {
+        let clicked = ActionResult::Clicked {
+            action: "allow".into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&clicked).unwrap(),
+            serde_json::json!({"status": "clicked", "action": "allow"})
+        );
+        assert_eq!(
+            serde_json::to_value(ActionResult::Pending).unwrap(),
+            serde_json::json!({"status": "pending"})
+        );
+        let back: ActionResult = serde_json::from_str(r#"{"status":"dismissed"}"#).unwrap();
+        assert_eq!(back, ActionResult::Dismissed);
+    }
+
+    #[test]
+    fn action_result_only_pending_is_unsettled() {
+        assert!(!ActionResult::Pending.is_settled());
+        assert!(ActionResult::Dismissed.is_settled());
+        assert!(ActionResult::Unknown.is_settled());
+    }
+
+    #[test]
+    fn notify_reply_without_ticket() {
+        let reply: NotifyReply = serde_json::from_str("{}").unwrap();
+        assert!(reply.ticket.is_none());
+    }
+
+    #[test]
+    fn action_poll_rejects_notify_requests() {
+        // The pipe server tells poll frames and notify frames apart this way
+        let json = r#"{"pid":0,"event":"task_complete","poll":"x"}"#;
+        assert!(serde_json::from_str::<ActionPoll>(json).is_err());
+        let poll: ActionPoll = serde_json::from_str(r#"{"poll":"t-1"}"#).unwrap();
+        assert_eq!(poll.poll, "t-1");
+    }
+
+    #[test]
+    fn toast_action_parses_cli_form() {
+        let a: ToastAction = "allow=Allow once".parse().unwrap();
+        assert_eq!(a.id, "allow");
+        assert_eq!(a.label, "Allow once");
+        let bare: ToastAction = "deny".parse().unwrap();
+        assert_eq!(bare.label, "deny");
+        assert!("=Label".parse::<ToastAction>().is_err());
+        assert!("id=".parse::<ToastAction>().is_err());
+    }
+
+    #[test]
+    fn priority_missing_is_normal() {
+        let json = r#"{"pid":0,"event":"task_complete"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.priority, Priority::Normal);
+    }
+
+    #[test]
+    fn priority_roundtrip_lowercase() {
+        let mut req = make_request("error");
+        req.priority = Priority::Critical;
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""priority":"critical""#));
+        let back: NotifyRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.priority, Priority::Critical);
+    }
+
+    #[test]
+    fn priority_unknown_value_reads_as_normal() {
+        let json = r#"{"pid":0,"event":"task_complete","priority":"urgent"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.priority, Priority::Normal);
+    }
+
+    #[test]
+    fn priority_parses_cli_values_case_insensitively() {
+        assert_eq!("HIGH".parse::<Priority>(), Ok(Priority::High));
+        assert_eq!("low".parse::<Priority>(), Ok(Priority::Low));
+        assert!("urgent".parse::<Priority>().is_err());
+        assert!(Priority::Low < Priority::Normal && Priority::High < Priority::Critical);
+    }
+
+    #[test]
+    fn downgrade_for_legacy_receiver_resets_priority() {
+        let mut req = make_request("error");
+        req.priority = Priority::High;
+        assert_eq!(req.required_features(), vec!["priority"]);
+        let dropped = req.downgrade_for(&Capabilities::legacy());
+        assert_eq!(dropped, vec!["priority"]);
+        assert_eq!(req.priority, Priority::Normal);
+    }
+
+    #[test]
+    fn extra_field_parses_cli_form() {
+        assert_eq!(
+            parse_extra_field("branch = feat/x=y"),
+            Ok(("branch".into(), "feat/x=y".into()))
+        );
+        assert_eq!(parse_extra_field("empty="), Ok(("empty".into(), "".into())));
+        assert!(parse_extra_field("novalue").is_err());
+        assert!(parse_extra_field(" =v").is_err());
+    }
+
+    #[test]
+    fn extra_missing_is_empty_and_roundtrips() {
+        let json = r#"{"pid":0,"event":"task_complete"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert!(req.extra.is_empty());
+
+        let mut req = make_request("task_complete");
+        req.extra.insert("ticket".into(), "ABC-1".into());
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains(r#""extra":{"ticket":"ABC-1"}"#));
+        let back: NotifyRequest = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.extra, req.extra);
+    }
+
+    #[test]
+    fn downgrade_for_legacy_receiver_drops_extra() {
+        let mut req = make_request("task_complete");
+        req.extra.insert("branch".into(), "main".into());
+        assert_eq!(req.required_features(), vec!["extra"]);
+        assert_eq!(req.downgrade_for(&Capabilities::legacy()), vec!["extra"]);
+        assert!(req.extra.is_empty());
+    }
+
+    #[test]
+    fn hook_context_is_flattened_on_the_wire() {
+        let mut req = make_request("task_complete");
+        req.context = HookContext {
+            session_id: Some("s-1".into()),
+            cwd: Some("/home/me/proj".into()),
+            transcript_path: None,
+            hook_event_name: Some("Stop".into()),
+            sent_at: Some(1_700_000_000_000),
+        };
+        let v = serde_json::to_value(&req).unwrap();
+        assert_eq!(v["session_id"], "s-1");
+        assert_eq!(v["cwd"], "/home/me/proj");
+        assert_eq!(v["sent_at"], 1_700_000_000_000u64);
+        let back: NotifyRequest = serde_json::from_value(v).unwrap();
+        assert_eq!(back.context, req.context);
+    }
+
+    #[test]
+    fn hook_context_missing_is_default() {
+        let json = r#"{"pid":0,"event":"task_complete"}"#;
+        let req: NotifyRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.context, HookContext::default());
+    }
+
+    #[test]
+    fn hook_context_from_hook_payload() {
+        let hook = serde_json::json!({
+            "session_id": "abc",
+            "cwd": "C:\\work\\proj",
+            "transcript_path": "/t.jsonl",
+            "hook_event_name": "Stop",
+            "tool_name": "Bash"
+        });
+        let ctx = HookContext::from_hook(&hook);
+        assert_eq!(ctx.session_id.as_deref(), Some("abc"));
+        assert_eq!(ctx.hook_event_name.as_deref(), Some("Stop"));
+        assert_eq!(ctx.transcript_path.as_deref(), Some("/t.jsonl"));
+        assert_eq!(ctx.cwd_name().as_deref(), Some("proj"));
+        assert!(ctx.sent_at.is_none());
+        assert!(HookContext::from_hook(&serde_json::json!({"cwd": " "}))
+            .cwd
+            .is_none());
+    }
+
+    #[test]
+    fn hook_context_stamped_sets_sent_at() {
+        assert!(HookContext::default().stamped().sent_at.unwrap() > 0);
+    }
+
+    #[test]
+    fn hook_context_is_advisory_and_survives_downgrade() {
+        let mut req = make_request("task_complete");
+        req.context.session_id = Some("s".into());
+        assert!(req.required_features().is_empty());
+        req.downgrade_for(&Capabilities::legacy());
+        assert_eq!(req.context.session_id.as_deref(), Some("s"));
+    }
+
+    #[test]
+    fn attach_hook_context_keeps_sent_at_and_fills_title() {
+        let mut req = make_request("task_complete");
+        req.title_hint = None;
+        req.context.sent_at = Some(42);
+        req.attach_hook_context(&serde_json::json!({"session_id": "s", "cwd": "/a/b"}));
+        assert_eq!(req.context.sent_at, Some(42));
+        assert_eq!(req.context.session_id.as_deref(), Some("s"));
+        assert_eq!(req.title_hint.as_deref(), Some("b"));
+
+        // An explicit title wins over the cwd
+        req.title_hint = Some("mine".into());
+        req.attach_hook_context(&serde_json::json!({"cwd": "/a/c"}));
+        assert_eq!(req.title_hint.as_deref(), Some("mine"));
+    }
+
+    #[test]
+    fn notify_outcome_is_tagged_by_status() {
+        let accepted = NotifyOutcome::Accepted { ticket: None };
+        assert_eq!(
+            serde_json::to_value(&accepted).unwrap(),
+            serde_json::json!({"status": "accepted", "ticket": null})
+        );
+        let rejected: NotifyOutcome =
+            serde_json::from_str(r#"{"status":"rejected","error":"missing field `pid`"}"#).unwrap();
+        assert_eq!(
+            rejected,
+            NotifyOutcome::Rejected {
+                error: "missing field `pid`".into()
+            }
+        );
+        let dropped = NotifyOutcome::Dropped {
+            reason: DropReason::RateLimited,
+        };
+        assert_eq!(
+            serde_json::to_value(&dropped).unwrap(),
+            serde_json::json!({"status": "dropped", "reason": "rate_limited"})
+        );
+        assert!(DropReason::RateLimited.is_retryable());
+        assert!(!DropReason::Duplicate.is_retryable());
+    }
 }
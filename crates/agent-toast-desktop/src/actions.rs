@@ -0,0 +1,160 @@
+//! Ticket registry for toasts that carry action buttons.
+//!
+//! A toast shown from a request with `actions` gets a ticket. The sender polls
+//! the ticket (HTTP `GET /actions/<ticket>` or a pipe poll frame) until the
+//! toast settles: an action click, or any other close. Settled results are kept
+//! for [`RESULT_TTL`] so a slow poller still sees them.
+
+use agent_toast_core::wire::ActionResult;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a settled result stays pollable.
+pub const RESULT_TTL: Duration = Duration::from_secs(10 * 60);
+
+struct Entry {
+    notification_id: String,
+    result: ActionResult,
+    settled_at: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct ActionRegistry {
+    entries: HashMap<String, Entry>,
+}
+
+impl ActionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a ticket for a toast that is now on screen.
+    pub fn register(&mut self, notification_id: &str, now: Instant) -> String {
+        self.prune(now);
+        let ticket = uuid::Uuid::new_v4().to_string();
+        self.entries.insert(
+            ticket.clone(),
+            Entry {
+                notification_id: notification_id.to_string(),
+                result: ActionResult::Pending,
+                settled_at: None,
+            },
+        );
+        ticket
+    }
+
+    /// Settle the pending ticket of `notification_id`. The first result wins,
+    /// so the close that follows an action click doesn't overwrite it. Returns
+    /// `false` if there was nothing pending for that toast.
+    pub fn settle(&mut self, notification_id: &str, result: ActionResult, now: Instant) -> bool {
+        let Some(entry) = self
+            .entries
+            .values_mut()
+            .find(|e| e.notification_id == notification_id && e.settled_at.is_none())
+        else {
+            return false;
+        };
+        entry.result = result;
+        entry.settled_at = Some(now);
+        true
+    }
+
+    pub fn poll(&mut self, ticket: &str, now: Instant) -> ActionResult {
+        self.prune(now);
+        self.entries
+            .get(ticket)
+            .map(|e| e.result.clone())
+            .unwrap_or(ActionResult::Unknown)
+    }
+
+    fn prune(&mut self, now: Instant) {
+        self.entries.retain(|_, e| match e.settled_at {
+            Some(at) => now.duration_since(at) < RESULT_TTL,
+            None => true,
+        });
+    }
+}
+
+static REGISTRY: Lazy<Mutex<ActionRegistry>> = Lazy::new(|| Mutex::new(ActionRegistry::new()));
+
+pub fn register(notification_id: &str) -> String {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .register(notification_id, Instant::now())
+}
+
+pub fn settle(notification_id: &str, result: ActionResult) -> bool {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .settle(notification_id, result, Instant::now())
+}
+
+pub fn poll(ticket: &str) -> ActionResult {
+    REGISTRY.lock().unwrap().poll(ticket, Instant::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clicked(id: &str) -> ActionResult {
+        ActionResult::Clicked { action: id.into() }
+    }
+
+    #[test]
+    fn new_ticket_is_pending() {
+        let mut reg = ActionRegistry::new();
+        let now = Instant::now();
+        let ticket = reg.register("notify-1", now);
+        assert_eq!(reg.poll(&ticket, now), ActionResult::Pending);
+    }
+
+    #[test]
+    fn unknown_ticket_polls_unknown() {
+        let mut reg = ActionRegistry::new();
+        assert_eq!(reg.poll("nope", Instant::now()), ActionResult::Unknown);
+    }
+
+    #[test]
+    fn first_settle_wins() {
+        // 액션 클릭 직후 이어지는 close가 결과를 Dismissed로 덮어쓰면 안 됨
+        let mut reg = ActionRegistry::new();
+        let now = Instant::now();
+        let ticket = reg.register("notify-1", now);
+        assert!(reg.settle("notify-1", clicked("allow"), now));
+        assert!(!reg.settle("notify-1", ActionResult::Dismissed, now));
+        assert_eq!(reg.poll(&ticket, now), clicked("allow"));
+    }
+
+    #[test]
+    fn settle_without_ticket_is_noop() {
+        let mut reg = ActionRegistry::new();
+        assert!(!reg.settle("notify-9", ActionResult::Dismissed, Instant::now()));
+    }
+
+    #[test]
+    fn settled_results_expire_after_ttl() {
+        let mut reg = ActionRegistry::new();
+        let now = Instant::now();
+        let ticket = reg.register("notify-1", now);
+        reg.settle("notify-1", ActionResult::Dismissed, now);
+        assert_eq!(
+            reg.poll(&ticket, now + RESULT_TTL - Duration::from_secs(1)),
+            ActionResult::Dismissed
+        );
+        assert_eq!(reg.poll(&ticket, now + RESULT_TTL), ActionResult::Unknown);
+    }
+
+    #[test]
+    fn pending_tickets_never_expire() {
+        let mut reg = ActionRegistry::new();
+        let now = Instant::now();
+        let ticket = reg.register("notify-1", now);
+        let later = now + RESULT_TTL * 3;
+        assert_eq!(reg.poll(&ticket, later), ActionResult::Pending);
+    }
+}
@@ -1,5 +1,8 @@
 use clap::Parser;
 
+/// Default `--decide-timeout`, in seconds.
+pub const DECIDE_TIMEOUT_SECS: u64 = 50;
+
 pub use agent_toast_core::NotifyRequest;
 
 #[derive(Parser, Debug)]
@@ -25,10 +28,44 @@ pub struct Cli {
     #[arg(long)]
     pub dynamic: bool,
 
+    /// Body from the hook's stdin JSON, e.g. "{tool_name}: {tool_input.command|80}".
+    /// Takes precedence over --dynamic; falls back to --message when empty.
+    #[arg(long)]
+    pub template: Option<String>,
+
     /// Window title hint for matching the correct source window
     #[arg(long)]
     pub title: Option<String>,
 
+    /// Idempotency key: only the first notification with this key is shown
+    /// within the dedupe window (hook retries, duplicate hooks)
+    #[arg(long)]
+    pub dedupe_key: Option<String>,
+
+    /// Action button as ID=LABEL (repeatable); waits and prints the clicked id
+    #[arg(long = "action", value_name = "ID=LABEL")]
+    pub actions: Vec<agent_toast_core::wire::ToastAction>,
+
+    /// Seconds to wait for an action click
+    #[arg(long, default_value_t = 120)]
+    pub action_timeout: u64,
+
+    /// Urgency: low | normal | high | critical
+    #[arg(long, default_value = "normal")]
+    pub priority: agent_toast_core::wire::Priority,
+
+    /// Metadata row shown under the message as KEY=VALUE (repeatable)
+    #[arg(long = "field", value_name = "KEY=VALUE", value_parser = agent_toast_core::wire::parse_extra_field)]
+    pub fields: Vec<(String, String)>,
+
+    /// PermissionRequest hook: show Allow/Deny and print the hook decision JSON
+    #[arg(long)]
+    pub decide: bool,
+
+    /// Seconds to wait for Allow/Deny before deferring to Claude's own prompt
+    #[arg(long, default_value_t = DECIDE_TIMEOUT_SECS)]
+    pub decide_timeout: u64,
+
     /// Start as background daemon (no notification)
     #[arg(long)]
     pub daemon: bool,
@@ -48,6 +85,16 @@ pub struct Cli {
     /// Positional argument for Codex JSON payload
     #[arg(index = 1)]
     pub codex_json: Option<String>,
+
+    /// Codex mode: one argument of the user's previous notify program, which
+    /// gets the payload forwarded (`codex_config::CHAIN_FLAG`)
+    #[arg(long = "chain", value_name = "ARG", allow_hyphen_values = true)]
+    pub chain: Vec<String>,
+
+    /// Marks a hook command as registered by agent-toast
+    /// (`hook_config::MARKER`); otherwise ignored.
+    #[arg(long = "agent-toast-managed", hide = true)]
+    pub managed: bool,
 }
 
 #[cfg(test)]
@@ -111,12 +158,124 @@ mod tests {
         assert!(!cli.daemon);
     }
 
+    #[test]
+    fn cli_parse_dedupe_key() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--event",
+            "task_complete",
+            "--dedupe-key",
+            "turn-1",
+        ])
+        .unwrap();
+        assert_eq!(cli.dedupe_key.as_deref(), Some("turn-1"));
+
+        let cli = Cli::try_parse_from(["agent-toast", "--event", "task_complete"]).unwrap();
+        assert!(cli.dedupe_key.is_none());
+    }
+
+    #[test]
+    fn cli_parse_actions() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--event",
+            "user_input_required",
+            "--action",
+            "allow=허용",
+            "--action",
+            "deny",
+        ])
+        .unwrap();
+        assert_eq!(cli.actions.len(), 2);
+        assert_eq!(cli.actions[0].label, "허용");
+        assert_eq!(cli.actions[1].id, "deny");
+        assert_eq!(cli.action_timeout, 120);
+
+        assert!(Cli::try_parse_from(["agent-toast", "--action", "=x"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_decide_flag() {
+        let cli =
+            Cli::try_parse_from(["agent-toast", "--event", "user_input_required", "--decide"])
+                .unwrap();
+        assert!(cli.decide);
+        assert_eq!(cli.decide_timeout, 50);
+    }
+
+    #[test]
+    fn cli_parse_template() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--event",
+            "user_input_required",
+            "--template",
+            "{tool_name}: {tool_input.command|80}",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.template.as_deref(),
+            Some("{tool_name}: {tool_input.command|80}")
+        );
+    }
+
+    #[test]
+    fn cli_parse_fields() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--event",
+            "task_complete",
+            "--field",
+            "branch=main",
+            "--field",
+            "job = 42",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.fields,
+            vec![
+                ("branch".into(), "main".into()),
+                ("job".into(), "42".into())
+            ]
+        );
+        assert!(Cli::try_parse_from(["agent-toast", "--field", "novalue"]).is_err());
+    }
+
+    #[test]
+    fn cli_parse_priority() {
+        use agent_toast_core::wire::Priority;
+        let cli =
+            Cli::try_parse_from(["agent-toast", "--event", "error", "--priority", "high"]).unwrap();
+        assert_eq!(cli.priority, Priority::High);
+
+        let cli = Cli::try_parse_from(["agent-toast", "--event", "error"]).unwrap();
+        assert_eq!(cli.priority, Priority::Normal);
+
+        assert!(Cli::try_parse_from(["agent-toast", "--priority", "urgent"]).is_err());
+    }
+
     #[test]
     fn cli_parse_codex_flag() {
         let cli = Cli::try_parse_from(["agent-toast", "--codex"]).unwrap();
         assert!(cli.codex);
     }
 
+    #[test]
+    fn cli_parse_codex_with_chain() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--codex",
+            "--chain",
+            "python3",
+            "--chain",
+            "-u",
+            r#"{"type":"test"}"#,
+        ])
+        .unwrap();
+        assert_eq!(cli.chain, ["python3", "-u"]);
+        assert_eq!(cli.codex_json.as_deref(), Some(r#"{"type":"test"}"#));
+    }
+
     #[test]
     fn cli_parse_codex_with_json_payload() {
         let cli = Cli::try_parse_from(["agent-toast", "--codex", r#"{"type":"test"}"#]).unwrap();
@@ -221,4 +380,19 @@ mod tests {
         let cli = Cli::try_parse_from(["agent-toast", "--event", "task_complete"]).unwrap();
         assert!(!cli.dynamic);
     }
+
+    #[test]
+    fn cli_accepts_hook_marker() {
+        let cli = Cli::try_parse_from([
+            "agent-toast",
+            "--event",
+            "task_complete",
+            agent_toast_core::hook_config::MARKER,
+        ])
+        .unwrap();
+        assert!(cli.managed);
+        let cli =
+            Cli::try_parse_from(["agent-toast", "--daemon", "--agent-toast-managed"]).unwrap();
+        assert!(cli.daemon);
+    }
 }
@@ -1,4 +1,7 @@
 use crate::cli::NotifyRequest;
+use agent_toast_core::wire::{
+    Capabilities, NotifyOutcome, NotifyReply, MAX_BATCH_ITEMS, SCHEMA_VERSION,
+};
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -6,6 +9,17 @@ use std::time::Duration;
 
 pub const MAX_BODY_BYTES: usize = 64 * 1024;
 pub const HTTP_PATH: &str = "/notify";
+/// `POST` a JSON array of requests here; answers `200` with one
+/// [`NotifyOutcome`] per item.
+pub const BATCH_PATH: &str = "/notify/batch";
+/// Body limit for [`BATCH_PATH`] (the per-request limit stays `MAX_BODY_BYTES`).
+pub const MAX_BATCH_BODY_BYTES: usize = 16 * MAX_BODY_BYTES;
+/// `GET` here returns this build's [`Capabilities`] so senders can downgrade
+/// payloads for older desktops.
+pub const CAPABILITIES_PATH: &str = "/capabilities";
+/// `GET /actions/<ticket>` returns the ticket's
+/// [`ActionResult`](agent_toast_core::wire::ActionResult).
+pub const ACTIONS_PATH_PREFIX: &str = "/actions/";
 const POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Handle to a running HTTP server. Drop or call `stop()` to terminate the
@@ -27,9 +41,15 @@ impl HttpHandle {
 
 /// Bind synchronously and spawn the receive loop. Returns an error if the
 /// address cannot be bound — caller can surface this to the UI immediately.
+///
+/// `on_request` gets one request for `POST /notify` and every valid item of a
+/// `POST /notify/batch`, so a batch can be admitted as a unit. It returns one
+/// [`NotifyOutcome`] per request; for a single request the ticket of a shown
+/// toast with buttons is sent back as a `202` [`NotifyReply`] (otherwise
+/// `204`).
 pub fn start_server<F>(bind_addr: &str, on_request: F) -> Result<HttpHandle, String>
 where
-    F: Fn(NotifyRequest) + Send + 'static,
+    F: Fn(Vec<NotifyRequest>) -> Vec<NotifyOutcome> + Send + 'static,
 {
     let server = tiny_http::Server::http(bind_addr).map_err(|e| e.to_string())?;
     let stop = Arc::new(AtomicBool::new(false));
@@ -95,47 +115,58 @@ fn wake_targets(bind_addr: &str) -> Vec<SocketAddr> {
 
 fn handle_request<F>(mut req: tiny_http::Request, on_request: &F)
 where
-    F: Fn(NotifyRequest),
+    F: Fn(Vec<NotifyRequest>) -> Vec<NotifyOutcome>,
 {
     use tiny_http::{Method, Response, StatusCode};
 
-    if req.method() != &Method::Post || req.url() != HTTP_PATH {
-        let _ = req.respond(Response::empty(StatusCode(404)));
+    if req.method() == &Method::Get && req.url() == CAPABILITIES_PATH {
+        respond_capabilities(req);
         return;
     }
 
-    let body_len = req.body_length();
-    match body_len {
-        Some(n) if n > MAX_BODY_BYTES => {
-            let _ = req.respond(Response::empty(StatusCode(413)));
-            return;
-        }
-        Some(_) => {}
-        None => {
-            let _ = req.respond(Response::empty(StatusCode(411)));
+    if req.method() == &Method::Get {
+        if let Some(ticket) = req.url().strip_prefix(ACTIONS_PATH_PREFIX) {
+            let ticket = ticket.to_string();
+            respond_action_poll(req, &ticket);
             return;
         }
     }
 
-    let mut buf = Vec::with_capacity(body_len.unwrap_or(0));
-    use std::io::Read;
-    let (read_ok, too_large) = {
-        let reader = req.as_reader();
-        let read_result = reader
-            .take((MAX_BODY_BYTES as u64) + 1)
-            .read_to_end(&mut buf);
-        (read_result.is_ok(), buf.len() > MAX_BODY_BYTES)
-    };
+    if req.method() == &Method::Post && req.url() == BATCH_PATH {
+        handle_batch(req, on_request);
+        return;
+    }
 
-    if !read_ok || too_large {
-        let _ = req.respond(Response::empty(StatusCode(400)));
+    if req.method() != &Method::Post || req.url() != HTTP_PATH {
+        let _ = req.respond(Response::empty(StatusCode(404)));
         return;
     }
 
+    let buf = match read_body(&mut req, MAX_BODY_BYTES) {
+        Ok(buf) => buf,
+        Err(status) => {
+            let _ = req.respond(Response::empty(status));
+            return;
+        }
+    };
+
     match serde_json::from_slice::<NotifyRequest>(&buf) {
         Ok(parsed) => {
-            on_request(parsed);
-            let _ = req.respond(Response::empty(StatusCode(204)));
+            warn_if_newer(&parsed);
+            match on_request(vec![parsed]).into_iter().next() {
+                Some(NotifyOutcome::Accepted {
+                    ticket: Some(ticket),
+                }) => respond_json(
+                    req,
+                    StatusCode(202),
+                    &NotifyReply {
+                        ticket: Some(ticket),
+                    },
+                ),
+                _ => {
+                    let _ = req.respond(Response::empty(StatusCode(204)));
+                }
+            }
         }
         Err(e) => {
             log::warn!("[HTTP] JSON parse error: {e}");
@@ -144,6 +175,133 @@ where
     }
 }
 
+/// `POST /notify/batch`: items are parsed one by one so a single bad entry
+/// is reported as [`NotifyOutcome::Rejected`] instead of failing the batch.
+/// Valid items get the outcome `on_request` reports for them.
+fn handle_batch<F>(mut req: tiny_http::Request, on_request: &F)
+where
+    F: Fn(Vec<NotifyRequest>) -> Vec<NotifyOutcome>,
+{
+    use tiny_http::{Response, StatusCode};
+
+    let buf = match read_body(&mut req, MAX_BATCH_BODY_BYTES) {
+        Ok(buf) => buf,
+        Err(status) => {
+            let _ = req.respond(Response::empty(status));
+            return;
+        }
+    };
+    let items = match serde_json::from_slice::<Vec<serde_json::Value>>(&buf) {
+        Ok(items) => items,
+        Err(e) => {
+            log::warn!("[HTTP] batch JSON parse error: {e}");
+            let _ = req.respond(Response::empty(StatusCode(400)));
+            return;
+        }
+    };
+    if items.len() > MAX_BATCH_ITEMS {
+        let _ = req.respond(Response::empty(StatusCode(413)));
+        return;
+    }
+
+    let mut outcomes = Vec::with_capacity(items.len());
+    let mut valid = Vec::new();
+    for item in items {
+        match serde_json::from_value::<NotifyRequest>(item) {
+            Ok(parsed) => {
+                warn_if_newer(&parsed);
+                valid.push(parsed);
+                outcomes.push(NotifyOutcome::Accepted { ticket: None });
+            }
+            Err(e) => outcomes.push(NotifyOutcome::Rejected {
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    let mut shown = on_request(valid).into_iter();
+    for outcome in &mut outcomes {
+        if matches!(outcome, NotifyOutcome::Accepted { .. }) {
+            *outcome = shown
+                .next()
+                .unwrap_or(NotifyOutcome::Accepted { ticket: None });
+        }
+    }
+    respond_json(req, StatusCode(200), &outcomes);
+}
+
+/// Read at most `limit` bytes of body, or the status to answer with.
+fn read_body(req: &mut tiny_http::Request, limit: usize) -> Result<Vec<u8>, tiny_http::StatusCode> {
+    use std::io::Read;
+    use tiny_http::StatusCode;
+
+    let body_len = match req.body_length() {
+        Some(n) if n > limit => return Err(StatusCode(413)),
+        Some(n) => n,
+        None => return Err(StatusCode(411)),
+    };
+
+    let mut buf = Vec::with_capacity(body_len);
+    let read_result = req
+        .as_reader()
+        .take((limit as u64) + 1)
+        .read_to_end(&mut buf);
+    if read_result.is_err() || buf.len() > limit {
+        return Err(StatusCode(400));
+    }
+    Ok(buf)
+}
+
+fn warn_if_newer(parsed: &NotifyRequest) {
+    if parsed.schema_version > SCHEMA_VERSION {
+        log::warn!(
+            "[HTTP] sender schema v{} is newer than ours (v{}); unknown fields are ignored",
+            parsed.schema_version,
+            SCHEMA_VERSION
+        );
+    }
+}
+
+fn respond_capabilities(req: tiny_http::Request) {
+    respond_json(req, tiny_http::StatusCode(200), &Capabilities::current());
+}
+
+/// Unknown or expired tickets answer `404` so a poller can stop.
+fn respond_action_poll(req: tiny_http::Request, ticket: &str) {
+    use agent_toast_core::wire::ActionResult;
+
+    match crate::actions::poll(ticket) {
+        ActionResult::Unknown => {
+            let _ = req.respond(tiny_http::Response::empty(tiny_http::StatusCode(404)));
+        }
+        result => respond_json(req, tiny_http::StatusCode(200), &result),
+    }
+}
+
+fn respond_json<T: serde::Serialize>(
+    req: tiny_http::Request,
+    status: tiny_http::StatusCode,
+    value: &T,
+) {
+    use tiny_http::{Header, Response, StatusCode};
+
+    let body = match serde_json::to_vec(value) {
+        Ok(b) => b,
+        Err(e) => {
+            log::warn!("[HTTP] response serialize failed: {e}");
+            let _ = req.respond(Response::empty(StatusCode(500)));
+            return;
+        }
+    };
+    let header =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let _ = req.respond(
+        Response::from_data(body)
+            .with_status_code(status)
+            .with_header(header),
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,8 +318,13 @@ mod tests {
         let (tx, rx) = mpsc::channel();
         let port = pick_free_port();
         let addr = format!("127.0.0.1:{}", port);
-        let handle = start_server(&addr, move |req| {
-            tx.send(req).ok();
+        let handle = start_server(&addr, move |reqs| {
+            reqs.into_iter()
+                .map(|req| {
+                    tx.send(req).ok();
+                    NotifyOutcome::Accepted { ticket: None }
+                })
+                .collect()
         })
         .expect("bind should succeed");
         std::thread::sleep(Duration::from_millis(100));
@@ -183,6 +346,26 @@ mod tests {
         assert_eq!(status_of(resp), 404);
     }
 
+    #[test]
+    fn capabilities_endpoint_reports_schema_version() {
+        let (port, _rx, _h) = bind_test_server();
+        let resp = ureq::get(&format!("http://127.0.0.1:{}/capabilities", port))
+            .call()
+            .expect("capabilities should be served");
+        assert_eq!(resp.status(), 200);
+        let caps: Capabilities = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(caps, Capabilities::current());
+    }
+
+    #[test]
+    fn capabilities_endpoint_rejects_post() {
+        let (port, _rx, _h) = bind_test_server();
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/capabilities", port))
+            .set("Content-Type", "application/json")
+            .send_string("{}");
+        assert_eq!(status_of(resp), 404);
+    }
+
     #[test]
     fn rejects_wrong_path() {
         let (port, _rx, _h) = bind_test_server();
@@ -207,6 +390,169 @@ mod tests {
         assert_eq!(received.hostname.as_deref(), Some("box1"));
     }
 
+    #[test]
+    fn batch_reports_outcome_per_item() {
+        let (port, rx, _h) = bind_test_server();
+        let body = r#"[
+            {"pid":0,"event":"task_complete","hostname":"box1"},
+            {"event":"missing pid"},
+            {"pid":0,"event":"error","hostname":"box1"}
+        ]"#;
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify/batch", port))
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .expect("batch should succeed");
+        assert_eq!(resp.status(), 200);
+        let outcomes: Vec<NotifyOutcome> =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes[0], NotifyOutcome::Accepted { ticket: None });
+        assert!(matches!(outcomes[1], NotifyOutcome::Rejected { .. }));
+        assert_eq!(outcomes[2], NotifyOutcome::Accepted { ticket: None });
+
+        // 유효한 항목만 순서대로 콜백에 전달
+        let first = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        let second = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(first.event, "task_complete");
+        assert_eq!(second.event, "error");
+    }
+
+    #[test]
+    fn batch_maps_tickets_to_accepted_items() {
+        let port = pick_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let _h = start_server(&addr, |reqs| {
+            reqs.iter()
+                .map(|r| NotifyOutcome::Accepted {
+                    ticket: (!r.actions.is_empty()).then(|| "t-1".to_string()),
+                })
+                .collect()
+        })
+        .expect("bind");
+        std::thread::sleep(Duration::from_millis(100));
+        let body = r#"[
+            {"bad":true},
+            {"pid":0,"event":"user_input_required","actions":[{"id":"a","label":"A"}]}
+        ]"#;
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify/batch", port))
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .unwrap();
+        let outcomes: Vec<NotifyOutcome> =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert!(matches!(outcomes[0], NotifyOutcome::Rejected { .. }));
+        assert_eq!(
+            outcomes[1],
+            NotifyOutcome::Accepted {
+                ticket: Some("t-1".into())
+            }
+        );
+    }
+
+    #[test]
+    fn batch_reports_items_the_desktop_dropped() {
+        use agent_toast_core::wire::DropReason;
+
+        let port = pick_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let _h = start_server(&addr, |reqs| {
+            reqs.iter()
+                .map(|r| match r.event.as_str() {
+                    "error" => NotifyOutcome::Accepted { ticket: None },
+                    _ => NotifyOutcome::Dropped {
+                        reason: DropReason::RateLimited,
+                    },
+                })
+                .collect()
+        })
+        .expect("bind");
+        std::thread::sleep(Duration::from_millis(100));
+        let body = r#"[
+            {"pid":0,"event":"task_complete"},
+            {"nope":1},
+            {"pid":0,"event":"error"}
+        ]"#;
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify/batch", port))
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .unwrap();
+        let outcomes: Vec<NotifyOutcome> =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(
+            outcomes[0],
+            NotifyOutcome::Dropped {
+                reason: DropReason::RateLimited
+            }
+        );
+        assert!(matches!(outcomes[1], NotifyOutcome::Rejected { .. }));
+        assert_eq!(outcomes[2], NotifyOutcome::Accepted { ticket: None });
+    }
+
+    #[test]
+    fn batch_rejects_non_array_body() {
+        let (port, _rx, _h) = bind_test_server();
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify/batch", port))
+            .set("Content-Type", "application/json")
+            .send_string(r#"{"pid":0,"event":"task_complete"}"#);
+        assert_eq!(status_of(resp), 400);
+    }
+
+    #[test]
+    fn batch_rejects_too_many_items() {
+        let (port, _rx, _h) = bind_test_server();
+        let items = vec![r#"{"pid":0,"event":"x"}"#; MAX_BATCH_ITEMS + 1];
+        let body = format!("[{}]", items.join(","));
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify/batch", port))
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+        assert_eq!(status_of(resp), 413);
+    }
+
+    #[test]
+    fn ticket_from_callback_is_returned_as_202() {
+        let port = pick_free_port();
+        let addr = format!("127.0.0.1:{}", port);
+        let _h = start_server(&addr, |reqs| {
+            vec![
+                NotifyOutcome::Accepted {
+                    ticket: Some("t-1".into())
+                };
+                reqs.len()
+            ]
+        })
+        .expect("bind");
+        std::thread::sleep(Duration::from_millis(100));
+        let body =
+            r#"{"pid":0,"event":"user_input_required","actions":[{"id":"allow","label":"Allow"}]}"#;
+        let resp = ureq::post(&format!("http://127.0.0.1:{}/notify", port))
+            .set("Content-Type", "application/json")
+            .send_string(body)
+            .expect("notify should succeed");
+        assert_eq!(resp.status(), 202);
+        let reply: NotifyReply = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(reply.ticket.as_deref(), Some("t-1"));
+    }
+
+    #[test]
+    fn action_poll_reports_pending_ticket() {
+        use agent_toast_core::wire::ActionResult;
+
+        let (port, _rx, _h) = bind_test_server();
+        let ticket = crate::actions::register("notify-http-poll");
+        let resp = ureq::get(&format!("http://127.0.0.1:{}/actions/{}", port, ticket))
+            .call()
+            .expect("known ticket should be served");
+        let result: ActionResult = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(result, ActionResult::Pending);
+    }
+
+    #[test]
+    fn action_poll_unknown_ticket_is_404() {
+        let (port, _rx, _h) = bind_test_server();
+        let resp = ureq::get(&format!("http://127.0.0.1:{}/actions/nope", port)).call();
+        assert_eq!(status_of(resp), 404);
+    }
+
     #[test]
     fn rejects_oversize_payload() {
         let (port, _rx, _h) = bind_test_server();
@@ -234,13 +580,13 @@ mod tests {
     fn stop_releases_port_for_rebind() {
         let port = pick_free_port();
         let addr = format!("127.0.0.1:{}", port);
-        let h1 = start_server(&addr, |_req| {}).expect("first bind");
+        let h1 = start_server(&addr, |_reqs| vec![]).expect("first bind");
         std::thread::sleep(Duration::from_millis(100));
         h1.stop();
         // Wait long enough for the recv_timeout loop to observe the stop flag
         // and release the socket.
         std::thread::sleep(POLL_INTERVAL + Duration::from_millis(500));
-        let h2 = start_server(&addr, |_req| {}).expect("rebind after stop");
+        let h2 = start_server(&addr, |_reqs| vec![]).expect("rebind after stop");
         h2.stop();
     }
 
@@ -251,11 +597,11 @@ mod tests {
     fn stop_releases_wildcard_bind() {
         let port = pick_free_port();
         let addr = format!("0.0.0.0:{}", port);
-        let h1 = start_server(&addr, |_req| {}).expect("first wildcard bind");
+        let h1 = start_server(&addr, |_reqs| vec![]).expect("first wildcard bind");
         std::thread::sleep(Duration::from_millis(100));
         h1.stop();
         std::thread::sleep(POLL_INTERVAL + Duration::from_millis(500));
-        let h2 = start_server(&addr, |_req| {}).expect("rebind 0.0.0.0 after stop");
+        let h2 = start_server(&addr, |_reqs| vec![]).expect("rebind 0.0.0.0 after stop");
         h2.stop();
     }
 
@@ -283,7 +629,7 @@ mod tests {
     fn duplicate_bind_returns_error() {
         let (port, _rx, _h) = bind_test_server();
         let addr = format!("127.0.0.1:{}", port);
-        let result = start_server(&addr, |_req| {});
+        let result = start_server(&addr, |_reqs| vec![]);
         assert!(result.is_err(), "second bind on same port must fail");
     }
 }
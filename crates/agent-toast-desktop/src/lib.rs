@@ -1,3 +1,4 @@
+pub mod actions;
 mod changelog;
 pub mod cli;
 mod fonts;
@@ -21,7 +22,7 @@ use std::sync::{Arc, Mutex};
 use cli::NotifyRequest;
 use notification::{
     close_notification, get_notification_for_window, on_foreground_changed, show_notification,
-    NotificationData, NotificationManagerState,
+    show_notification_batch, NotificationData, NotificationManagerState,
 };
 
 use tauri::image::Image;
@@ -64,8 +65,8 @@ pub fn sync_http_server(app: &AppHandle) -> Result<(), String> {
     if need_start {
         let handle = app.clone();
         let mgr_state = app.state::<NotificationManagerState>().inner().clone();
-        let new_handle = http_server::start_server(&want_addr, move |req| {
-            show_notification(&handle, &mgr_state, req);
+        let new_handle = http_server::start_server(&want_addr, move |reqs| {
+            show_notification_batch(&handle, &mgr_state, reqs)
         })?;
         *guard = Some(new_handle);
         log::info!("[HTTP] started on {}", want_addr);
@@ -219,6 +220,19 @@ fn activate_source(hwnd: isize, id: String, app: AppHandle) {
     }
 }
 
+/// Action button on a toast: settle the sender's ticket with the clicked id,
+/// then close. Counts as `activated` — the user acted on the toast.
+#[tauri::command]
+fn invoke_action(id: String, action: String, app: AppHandle) {
+    log::debug!("invoke_action called: id={}, action={}", id, action);
+    actions::settle(
+        &id,
+        agent_toast_core::wire::ActionResult::Clicked { action },
+    );
+    let state = app.state::<NotificationManagerState>();
+    close_notification(&app, &state, &id, stats::CloseReason::Activated);
+}
+
 #[tauri::command]
 fn test_notification(app: AppHandle, title: Option<String>, message: Option<String>) {
     log::debug!("[TEST] test_notification command called");
@@ -245,6 +259,12 @@ fn test_notification(app: AppHandle, title: Option<String>, message: Option<Stri
         process_tree: Some(vec![]),
         source: "claude".into(),
         hostname: None,
+        schema_version: agent_toast_core::wire::SCHEMA_VERSION,
+        dedupe_key: None,
+        actions: vec![],
+        priority: agent_toast_core::wire::Priority::Normal,
+        context: agent_toast_core::wire::HookContext::default(),
+        extra: Default::default(),
     };
     log::debug!("[TEST] Spawning notification thread for event={}", event);
     std::thread::spawn(move || {
@@ -415,6 +435,7 @@ pub fn run_app(initial_request: Option<NotifyRequest>, open_setup: bool) {
             close_notify,
             resize_notify,
             activate_source,
+            invoke_action,
             get_notification_data,
             test_notification,
             preview_notification_sound,
@@ -428,6 +449,10 @@ pub fn run_app(initial_request: Option<NotifyRequest>, open_setup: bool) {
             open_settings,
             setup::get_hook_config,
             setup::save_hook_config,
+            setup::preview_hook_config,
+            setup::list_settings_backups,
+            setup::restore_settings_backup,
+            setup::find_duplicate_hooks,
             setup::get_exe_path,
             setup::get_saved_exe_path,
             setup::open_settings_file,
@@ -520,9 +545,7 @@ pub fn run_app(initial_request: Option<NotifyRequest>, open_setup: bool) {
             // Start Named Pipe server for subsequent calls
             let pipe_handle = handle.clone();
             let pipe_state = state.clone();
-            pipe::start_server(move |req| {
-                show_notification(&pipe_handle, &pipe_state, req);
-            });
+            pipe::start_server(move |req| show_notification(&pipe_handle, &pipe_state, req));
 
             // Start HTTP receiver if enabled in settings
             if let Err(e) = sync_http_server(&handle) {
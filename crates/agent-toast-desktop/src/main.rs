@@ -78,48 +78,128 @@ fn try_acquire_singleton() -> Option<()> {
     Some(())
 }
 
+/// Start the background instance as a detached `--daemon-run` child.
+fn spawn_daemon() {
+    // Spawn detached daemon using CreateProcessW with bInheritHandles=FALSE.
+    // Rust's Command::spawn() always sets bInheritHandles=TRUE, which causes
+    // the child to inherit Claude Code's pipe handles — preventing hook completion.
+    #[cfg(windows)]
+    {
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            CreateProcessW, PROCESS_CREATION_FLAGS, PROCESS_INFORMATION, STARTUPINFOW,
+        };
+
+        let exe = std::env::current_exe().unwrap_or_default();
+        let cmd = format!("\"{}\" --daemon-run", exe.display());
+        let mut cmd_wide: Vec<u16> = cmd.encode_utf16().chain(std::iter::once(0)).collect();
+
+        let si = STARTUPINFOW {
+            cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+            ..Default::default()
+        };
+        let mut pi = PROCESS_INFORMATION::default();
+
+        const FLAGS: u32 = 0x0000_0200 // CREATE_NEW_PROCESS_GROUP
+                         | 0x0800_0000; // CREATE_NO_WINDOW
+
+        unsafe {
+            let ok = CreateProcessW(
+                None,
+                Some(windows::core::PWSTR(cmd_wide.as_mut_ptr())),
+                None,
+                None,
+                false, // bInheritHandles = FALSE
+                PROCESS_CREATION_FLAGS(FLAGS),
+                None,
+                None,
+                &si,
+                &mut pi,
+            );
+            if ok.is_ok() {
+                let _ = CloseHandle(pi.hProcess);
+                let _ = CloseHandle(pi.hThread);
+            }
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        let exe = std::env::current_exe().unwrap_or_default();
+        let _ = std::process::Command::new(&exe).arg("--daemon-run").spawn();
+    }
+}
+
+/// How long an action toast waits for a freshly spawned instance's pipe.
+const DAEMON_START_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// [`pipe::send_with_reply`], starting the background instance first when none
+/// is running: an action toast needs a live instance to report the click.
+/// Failures are reported on stderr, which the calling hook captures.
+fn send_with_reply_or_launch(
+    request: &NotifyRequest,
+) -> Option<agent_toast_core::wire::NotifyReply> {
+    match pipe::send_with_reply(request) {
+        Ok(Some(reply)) => return Some(reply),
+        Ok(None) => spawn_daemon(),
+        Err(e) => {
+            eprintln!("warn: action toast not delivered: {e}");
+            return None;
+        }
+    }
+    let deadline = std::time::Instant::now() + DAEMON_START_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        match pipe::send_with_reply(request) {
+            Ok(Some(reply)) => return Some(reply),
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("warn: action toast not delivered: {e}");
+                return None;
+            }
+        }
+    }
+    eprintln!(
+        "warn: action toast not delivered: agent-toast did not start within {}s",
+        DAEMON_START_TIMEOUT.as_secs()
+    );
+    None
+}
+
+/// Allow/Deny button label in the configured UI locale.
+fn decision_label(action: &str) -> String {
+    let ko = agent_toast_lib::setup::get_hook_config().locale == "ko";
+    match (action, ko) {
+        (agent_toast_core::permission::ALLOW_ACTION, true) => "허용".into(),
+        (agent_toast_core::permission::ALLOW_ACTION, false) => "Allow".into(),
+        (_, true) => "거부".into(),
+        (_, false) => "Deny".into(),
+    }
+}
+
 fn main() {
     let args = Cli::parse();
 
     // --codex mode: parse JSON from Codex CLI
     if args.codex {
         let json_str = args.codex_json.unwrap_or_default();
-        let codex_payload: serde_json::Value =
-            serde_json::from_str(&json_str).unwrap_or_else(|e| {
+        // The notify program this one replaced gets the payload too.
+        if let Err(e) = agent_toast_core::codex_config::forward(&args.chain, &json_str) {
+            error!("Failed to run chained notify program: {}", e);
+        }
+        let notification = agent_toast_core::codex::CodexNotification::parse(&json_str)
+            .unwrap_or_else(|e| {
                 error!("Failed to parse Codex JSON: {}", e);
                 std::process::exit(1);
             });
-
-        let codex_type = codex_payload["type"]
-            .as_str()
-            .unwrap_or("agent-turn-complete");
-        let event = codex_type.replace('-', "_");
-
-        let message = codex_payload["last-assistant-message"].as_str().map(|s| {
-            agent_toast_core::dynamic::truncate_chars(
-                s,
-                agent_toast_core::dynamic::MAX_MESSAGE_CHARS,
-            )
-        });
-
-        let title_hint = codex_payload["cwd"].as_str().map(|cwd| {
-            std::path::Path::new(cwd)
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| cwd.to_string())
-        });
+        let redactor = agent_toast_lib::setup::load_redactor();
 
         let pid = get_parent_pid();
         let process_tree = win32::get_process_tree(pid);
 
         let request = NotifyRequest {
             pid,
-            event,
-            message,
-            title_hint,
             process_tree: Some(process_tree),
-            source: "codex".into(),
-            hostname: None,
+            ..notification.to_request(&redactor)
         };
 
         match pipe::try_send(&request) {
@@ -161,57 +241,26 @@ fn main() {
             }
         }
 
-        // Spawn detached daemon using CreateProcessW with bInheritHandles=FALSE.
-        // Rust's Command::spawn() always sets bInheritHandles=TRUE, which causes
-        // the child to inherit Claude Code's pipe handles — preventing hook completion.
-        #[cfg(windows)]
-        {
-            use windows::Win32::Foundation::CloseHandle;
-            use windows::Win32::System::Threading::{
-                CreateProcessW, PROCESS_CREATION_FLAGS, PROCESS_INFORMATION, STARTUPINFOW,
-            };
-
-            let exe = std::env::current_exe().unwrap_or_default();
-            let cmd = format!("\"{}\" --daemon-run", exe.display());
-            let mut cmd_wide: Vec<u16> = cmd.encode_utf16().chain(std::iter::once(0)).collect();
-
-            let si = STARTUPINFOW {
-                cb: std::mem::size_of::<STARTUPINFOW>() as u32,
-                ..Default::default()
-            };
-            let mut pi = PROCESS_INFORMATION::default();
-
-            const FLAGS: u32 = 0x0000_0200 // CREATE_NEW_PROCESS_GROUP
-                             | 0x0800_0000; // CREATE_NO_WINDOW
-
-            unsafe {
-                let ok = CreateProcessW(
-                    None,
-                    Some(windows::core::PWSTR(cmd_wide.as_mut_ptr())),
-                    None,
-                    None,
-                    false, // bInheritHandles = FALSE
-                    PROCESS_CREATION_FLAGS(FLAGS),
-                    None,
-                    None,
-                    &si,
-                    &mut pi,
-                );
-                if ok.is_ok() {
-                    let _ = CloseHandle(pi.hProcess);
-                    let _ = CloseHandle(pi.hThread);
-                }
-            }
-        }
-        #[cfg(not(windows))]
-        {
-            let exe = std::env::current_exe().unwrap_or_default();
-            let _ = std::process::Command::new(&exe).arg("--daemon-run").spawn();
-        }
+        spawn_daemon();
         return;
     }
 
-    if args.setup || args.event.is_none() {
+    // Without --event, a hook invocation still names its event in the stdin
+    // JSON (`hook_event_name` / `notification_type`). A bare launch has no
+    // payload and opens the setup GUI.
+    let mut hook = None;
+    let event = match args.event.clone().filter(|e| !e.is_empty()) {
+        Some(event) => Some(event),
+        None if !args.setup => {
+            hook = agent_toast_core::dynamic::read_stdin_json();
+            hook.as_ref()
+                .and_then(agent_toast_core::hook_config::event_from_hook)
+                .map(str::to_string)
+        }
+        None => None,
+    };
+
+    let Some(event) = event.filter(|_| !args.setup) else {
         let _mutex = try_acquire_singleton();
         if _mutex.is_none() {
             // Another instance exists; try to signal it via pipe, then exit
@@ -221,7 +270,7 @@ fn main() {
         // No args or --setup: launch app with setup GUI (daemon also runs)
         agent_toast_lib::run_app(None, true);
         return;
-    }
+    };
 
     // --pid defaults to parent process PID (the shell that invoked us).
     // We use parent because this process may exit before the daemon reads the snapshot.
@@ -230,17 +279,34 @@ fn main() {
         debug!("auto pid: self={}, parent={}", std::process::id(), ppid);
         ppid
     });
-    let event = args
-        .event
-        .expect("--event is required when not using --daemon");
-
-    // With --dynamic, derive the body from the hook's stdin JSON; otherwise use
-    // the static --message as-is.
-    let message = if args.dynamic {
-        agent_toast_core::dynamic::resolve_from_stdin(args.message.as_deref())
-    } else {
-        args.message
-    };
+
+    // The hook's stdin JSON is read once, and only when it is used: for the
+    // event (above), the body (--template / --dynamic / --decide) and the
+    // session/cwd context it carries.
+    if hook.is_none() && (args.decide || args.dynamic || args.template.is_some()) {
+        hook = agent_toast_core::dynamic::read_stdin_json();
+    }
+
+    // --template: the user's own layout wins. --decide: the body describes
+    // the tool asking for permission. With --dynamic, derive it from the hook
+    // payload; otherwise use the static --message as-is.
+    let redactor = agent_toast_lib::setup::load_redactor();
+    let message = match &hook {
+        Some(h) if args.template.is_some() => agent_toast_core::dynamic::resolve_template(
+            h,
+            args.template.as_deref().unwrap_or_default(),
+            args.message.as_deref(),
+            &redactor,
+        ),
+        Some(h) if args.decide => {
+            agent_toast_core::permission::request_message(h, args.message.as_deref(), &redactor)
+        }
+        Some(h) => {
+            agent_toast_core::dynamic::resolve_message(h, args.message.as_deref(), &redactor)
+        }
+        None => args.message,
+    }
+    .map(|m| redactor.redact(&m));
 
     // Pre-resolve process tree while the process is still alive
     let process_tree = win32::get_process_tree(pid);
@@ -257,7 +323,7 @@ fn main() {
                 .unwrap_or(t)
         });
 
-    let request = NotifyRequest {
+    let mut request = NotifyRequest {
         pid,
         event,
         message,
@@ -265,7 +331,57 @@ fn main() {
         process_tree: Some(process_tree),
         source: "claude".into(),
         hostname: None,
+        schema_version: agent_toast_core::wire::SCHEMA_VERSION,
+        dedupe_key: args.dedupe_key.filter(|k| !k.is_empty()),
+        actions: if args.decide {
+            agent_toast_core::permission::decision_actions(
+                &decision_label(agent_toast_core::permission::ALLOW_ACTION),
+                &decision_label(agent_toast_core::permission::DENY_ACTION),
+            )
+        } else {
+            args.actions
+        },
+        priority: args.priority,
+        context: agent_toast_core::wire::HookContext::default().stamped(),
+        extra: args.fields.into_iter().collect(),
     };
+    if let Some(h) = &hook {
+        request.attach_hook_context(h);
+    }
+
+    // Dismissal, timeout or no running instance all mean "ask": print nothing
+    // and let Claude Code show its own prompt.
+    if args.decide {
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(args.decide_timeout);
+        let action = match pipe::send_with_reply(&request) {
+            Ok(Some(agent_toast_core::wire::NotifyReply {
+                ticket: Some(ticket),
+            })) => pipe::wait_for_action(&ticket, deadline),
+            _ => None,
+        };
+        let decision = agent_toast_core::permission::Decision::from_action(action.as_deref());
+        if let Some(output) = decision.hook_output() {
+            println!("{output}");
+        }
+        return;
+    }
+
+    // With actions, wait for the click and print its id for the calling hook.
+    // Unlike a plain toast, this one starts the app when it isn't running.
+    if !request.actions.is_empty() {
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(args.action_timeout);
+        if let Some(agent_toast_core::wire::NotifyReply {
+            ticket: Some(ticket),
+        }) = send_with_reply_or_launch(&request)
+        {
+            if let Some(action) = pipe::wait_for_action(&ticket, deadline) {
+                println!("{action}");
+            }
+        }
+        return;
+    }
 
     // Try to send to existing instance
     match pipe::try_send(&request) {
@@ -1,10 +1,15 @@
 use crate::cli::NotifyRequest;
 use crate::win32;
+use agent_toast_core::dynamic::truncate_chars;
+use agent_toast_core::wire::{
+    ActionResult, DropReason, NotifyOutcome, Priority, ToastAction, MAX_ACTIONS, MAX_EXTRA_FIELDS,
+    MAX_EXTRA_VALUE_CHARS,
+};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tauri::window::Color;
 use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 
@@ -52,6 +57,88 @@ impl RateLimiter {
 /// Spec §3.3: 10/s refill, burst 10.
 static GLOBAL_RATE_LIMITER: Lazy<RateLimiter> = Lazy::new(|| RateLimiter::new(10, 10));
 
+/// Whether `limiter` lets one request through. Every request pays its own
+/// token, batched or not, so a batch shows no more normal/low toasts than the
+/// limiter's burst; high and critical ones are shown even when it's empty.
+fn rate_admit(limiter: &RateLimiter, priority: Priority) -> bool {
+    limiter.try_consume() || !rate_limit_may_drop(priority)
+}
+
+/// Remembers recently shown `dedupe_key`s so hook retries and duplicate hook
+/// registrations don't stack identical toasts. Entries expire after the
+/// window passed to `is_recent`.
+pub struct DedupeCache {
+    seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl Default for DedupeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DedupeCache {
+    pub fn new() -> Self {
+        Self {
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns `true` if `key` was recorded within `window` (the caller
+    /// should drop the request). The window counts from the recorded
+    /// showing, so a steady stream of retries cannot keep a key alive forever.
+    pub fn is_recent(&self, key: &str, window: Duration, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, at| now.duration_since(*at) < window);
+        seen.contains_key(key)
+    }
+
+    /// Record `key` as shown at `now`. Only called once the toast is on
+    /// screen, so a request dropped for another reason can still be retried.
+    pub fn record(&self, key: &str, now: Instant) {
+        self.seen.lock().unwrap().insert(key.to_string(), now);
+    }
+}
+
+/// Shared across local (pipe) and remote (HTTP) paths, like the rate limiter.
+static RECENT_DEDUPE_KEYS: Lazy<DedupeCache> = Lazy::new(DedupeCache::new);
+
+/// Dedupe, then rate-limit `request` (`limiter` is `None` for the batch
+/// summary toast, which is never rate-limited). On admission returns the
+/// dedupe scope to [`DedupeCache::record`] once the toast is shown. Duplicates
+/// are checked first so they don't eat tokens.
+fn admit(
+    request: &NotifyRequest,
+    recent: &DedupeCache,
+    window_secs: u32,
+    limiter: Option<&RateLimiter>,
+    now: Instant,
+) -> Result<Option<String>, DropReason> {
+    let scope = dedupe_scope(request).filter(|_| window_secs > 0);
+    if let Some(scope) = &scope {
+        if recent.is_recent(scope, Duration::from_secs(window_secs.into()), now) {
+            return Err(DropReason::Duplicate);
+        }
+    }
+    if limiter.is_some_and(|l| !rate_admit(l, request.priority)) {
+        return Err(DropReason::RateLimited);
+    }
+    Ok(scope)
+}
+
+/// Cache key for a request's `dedupe_key`, scoped by source and sender host so
+/// two machines (or agents) reusing the same key don't suppress each other.
+/// `None` when the request carries no (non-empty) key.
+fn dedupe_scope(request: &NotifyRequest) -> Option<String> {
+    let key = request.dedupe_key.as_deref().filter(|k| !k.is_empty())?;
+    Some(format!(
+        "{}\u{1f}{}\u{1f}{}",
+        request.source,
+        request.hostname.as_deref().unwrap_or(""),
+        key
+    ))
+}
+
 /// Notification window width in logical pixels.
 /// Sized to fit title + message comfortably (min 200, max 600 for readability).
 const NOTIFICATION_WIDTH: f64 = 380.0;
@@ -77,22 +164,86 @@ pub struct NotificationData {
     /// Settings toggle — even if hostname is Some, UI may hide it.
     #[serde(default = "default_show_hostname")]
     pub show_hostname: bool,
+    /// Buttons rendered on the toast (already capped at `MAX_ACTIONS`).
+    #[serde(default)]
+    pub actions: Vec<ToastAction>,
+    /// 발신 훅의 세션 ID (같은 세션 알림 식별용)
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// 발신 측 전체 작업 경로. 같은 폴더명의 다른 체크아웃을 구분 (제목 툴팁)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// 메시지 아래 key/value 행 (`extra_rows` 로 정리된 상태)
+    #[serde(default)]
+    pub extra: Vec<(String, String)>,
 }
 
 fn default_show_hostname() -> bool {
     true
 }
 
+/// `NotifyRequest::extra` 를 토스트에 표시할 행으로 정리한다.
+/// 빈 키는 버리고, 키 순서대로 최대 `MAX_EXTRA_FIELDS` 개, 값은 `MAX_EXTRA_VALUE_CHARS` 자로 자른다.
+fn extra_rows(extra: BTreeMap<String, String>) -> Vec<(String, String)> {
+    extra
+        .into_iter()
+        .filter(|(k, _)| !k.trim().is_empty())
+        .take(MAX_EXTRA_FIELDS)
+        .map(|(k, v)| {
+            (
+                k.trim().to_string(),
+                truncate_chars(&v, MAX_EXTRA_VALUE_CHARS),
+            )
+        })
+        .collect()
+}
+
+/// low 우선순위 알림의 자동 닫힘 상한 (초). 자동 닫힘이 꺼져 있어도 적용된다.
+const LOW_PRIORITY_DISMISS_SECONDS: u32 = 5;
+
 /// update_available 알림은 클릭해야 설치가 진행되므로 자동 닫힘에서 제외(0=sticky).
-/// 그 외에는 설정값(`configured`)을 그대로 쓴다.
-fn resolve_auto_dismiss(source: &str, event: &str, configured: u32) -> u32 {
-    if source == "updater" && event == "update_available" {
-        0
-    } else {
-        configured
+/// 그 외에는 설정값(`configured`)을 우선순위로 보정한다:
+/// critical = sticky, high = 2배, low = 최대 5초.
+fn resolve_auto_dismiss(
+    source: &str,
+    event: &str,
+    has_actions: bool,
+    priority: Priority,
+    configured: u32,
+) -> u32 {
+    // 액션 버튼이 있는 알림은 발신 측이 응답을 기다리므로 자동 소멸하지 않음
+    if has_actions || (source == "updater" && event == "update_available") {
+        return 0;
+    }
+    match priority {
+        Priority::Critical => 0,
+        Priority::High => configured.saturating_mul(2),
+        Priority::Normal => configured,
+        Priority::Low if configured == 0 => LOW_PRIORITY_DISMISS_SECONDS,
+        Priority::Low => configured.min(LOW_PRIORITY_DISMISS_SECONDS),
     }
 }
 
+/// 알림 소리 재생 여부. low는 항상 무음, critical은 소리 설정이 꺼져 있어도 재생.
+fn should_play_sound(priority: Priority, sound_enabled: bool) -> bool {
+    match priority {
+        Priority::Low => false,
+        Priority::Normal | Priority::High => sound_enabled,
+        Priority::Critical => true,
+    }
+}
+
+/// rate limiter가 토큰 부족 시 이 알림을 버려도 되는지.
+/// high 이상은 토큰을 소비하되 부족해도 표시한다 (실패 알림이 폭주에 묻히지 않도록).
+fn rate_limit_may_drop(priority: Priority) -> bool {
+    priority < Priority::High
+}
+
+/// 소스 창이 이미 포커스돼 있을 때 건너뛸지. critical은 포커스 중에도 표시.
+fn skip_when_focused(priority: Priority) -> bool {
+    priority < Priority::Critical
+}
+
 /// 현재 화면에 update_available 알림이 떠 있는지. 주기 체크가 중복 생성하지 않도록 사용.
 fn has_active_update_toast(notifications: &[NotificationData]) -> bool {
     notifications
@@ -140,25 +291,128 @@ pub fn get_notification_for_window(
         .cloned()
 }
 
+/// Show several requests (a flushed backlog, replayed spool) in one call.
+/// Each is deduped and rate-limited like a single request, but the ones the
+/// rate limiter turns away are not lost: they are folded into one summary
+/// toast and reported as [`DropReason::Coalesced`]. Results are in request
+/// order.
+pub fn show_notification_batch(
+    app: &AppHandle,
+    state: &NotificationManagerState,
+    requests: Vec<NotifyRequest>,
+) -> Vec<NotifyOutcome> {
+    let mut overflow = Vec::new();
+    let mut outcomes: Vec<NotifyOutcome> = requests
+        .into_iter()
+        .enumerate()
+        .map(|(i, request)| {
+            let outcome = show(app, state, request.clone(), Some(&GLOBAL_RATE_LIMITER));
+            if outcome
+                == (NotifyOutcome::Dropped {
+                    reason: DropReason::RateLimited,
+                })
+            {
+                overflow.push((i, request));
+            }
+            outcome
+        })
+        .collect();
+    if overflow.is_empty() {
+        return outcomes;
+    }
+
+    let (indices, overflow): (Vec<usize>, Vec<NotifyRequest>) = overflow.into_iter().unzip();
+    let summary = overflow_summary(&overflow, &crate::setup::read_locale());
+    // A summary that isn't shown (source focused) leaves the items rate-limited,
+    // so the sender still knows to retry them.
+    if let NotifyOutcome::Accepted { .. } = show(app, state, summary, None) {
+        for i in indices {
+            outcomes[i] = NotifyOutcome::Dropped {
+                reason: DropReason::Coalesced,
+            };
+        }
+    }
+    outcomes
+}
+
+/// One toast standing in for the batch items in `overflow` (non-empty): "N
+/// more notifications", with a row per event. It takes the first item's
+/// source and window so clicking it still finds the terminal.
+fn overflow_summary(overflow: &[NotifyRequest], locale: &str) -> NotifyRequest {
+    let n = overflow.len();
+    let message = match locale {
+        "en" => format!("{n} more notifications"),
+        _ => format!("알림 {n}개가 더 있습니다"),
+    };
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for r in overflow {
+        *counts.entry(r.event_display().to_string()).or_default() += 1;
+    }
+    NotifyRequest {
+        message: Some(message),
+        dedupe_key: None,
+        actions: vec![],
+        priority: overflow
+            .iter()
+            .map(|r| r.priority)
+            .max()
+            .unwrap_or_default(),
+        extra: counts
+            .into_iter()
+            .map(|(event, count)| (event, count.to_string()))
+            .collect(),
+        ..overflow[0].clone()
+    }
+}
+
 pub fn show_notification(
     app: &AppHandle,
     state: &NotificationManagerState,
     request: NotifyRequest,
-) {
+) -> NotifyOutcome {
+    show(app, state, request, Some(&GLOBAL_RATE_LIMITER))
+}
+
+fn show(
+    app: &AppHandle,
+    state: &NotificationManagerState,
+    request: NotifyRequest,
+    limiter: Option<&RateLimiter>,
+) -> NotifyOutcome {
     let stats_state = app.state::<crate::stats::StatsState>().inner().clone();
     let ev = request.event.clone();
     let src = request.source.clone();
     let remote = request.hostname.is_some();
-
-    if !GLOBAL_RATE_LIMITER.try_consume() {
-        log::warn!(
-            "[RATE] dropped notification: event={} hostname={:?}",
-            request.event,
-            request.hostname
-        );
-        crate::stats::record_skipped_ratelimit(&stats_state, &ev, &src, remote);
-        return;
-    }
+    let priority = request.priority;
+
+    let window = crate::setup::get_hook_config().dedupe_window_seconds;
+    let dedupe = match admit(
+        &request,
+        &RECENT_DEDUPE_KEYS,
+        window,
+        limiter,
+        Instant::now(),
+    ) {
+        Ok(scope) => scope,
+        Err(reason) => {
+            if reason == DropReason::Duplicate {
+                log::debug!(
+                    "[NOTIFY] duplicate dedupe_key={:?} within {}s, skip",
+                    request.dedupe_key,
+                    window
+                );
+                crate::stats::record_skipped_duplicate(&stats_state, &ev, &src, remote);
+            } else {
+                log::warn!(
+                    "[RATE] dropped notification: event={} hostname={:?}",
+                    request.event,
+                    request.hostname
+                );
+                crate::stats::record_skipped_ratelimit(&stats_state, &ev, &src, remote);
+            }
+            return NotifyOutcome::Dropped { reason };
+        }
+    };
 
     log::debug!(
         "[NOTIFY] show_notification called: event={}, pid={}, source={}",
@@ -176,7 +430,9 @@ pub fn show_notification(
         };
         if already_shown {
             log::debug!("[NOTIFY] update_available already on screen, skip duplicate");
-            return;
+            return NotifyOutcome::Dropped {
+                reason: DropReason::Duplicate,
+            };
         }
     }
 
@@ -230,9 +486,11 @@ pub fn show_notification(
         // FR-2: Skip if source window is already focused (compare by HWND, not PID)
         let focused = win32::is_hwnd_focused(hwnd);
         log::debug!("[DEBUG] is_hwnd_focused({})={}", hwnd, focused);
-        if focused {
+        if focused && skip_when_focused(priority) {
             crate::stats::record_skipped_focused(&stats_state, &ev, &src, remote);
-            return;
+            return NotifyOutcome::Dropped {
+                reason: DropReason::Focused,
+            };
         }
 
         let title = {
@@ -262,6 +520,8 @@ pub fn show_notification(
     let auto_dismiss_seconds = resolve_auto_dismiss(
         &request.source,
         &request.event,
+        !request.actions.is_empty(),
+        request.priority,
         crate::setup::get_hook_config().auto_dismiss_seconds,
     );
 
@@ -276,6 +536,15 @@ pub fn show_notification(
         source: request.source.clone(),
         hostname: request.hostname.clone(),
         show_hostname: crate::setup::read_show_hostname(),
+        actions: request
+            .actions
+            .into_iter()
+            .filter(|a| !a.id.is_empty())
+            .take(MAX_ACTIONS)
+            .collect(),
+        session_id: request.context.session_id,
+        cwd: request.context.cwd,
+        extra: extra_rows(request.extra),
     };
 
     // Calculate position: stack from bottom-right
@@ -314,13 +583,19 @@ pub fn show_notification(
         match window {
             Ok(win) => {
                 log::debug!("[NOTIFY] Window created: id={}", id);
+                // Issue the ticket before the window can become visible, so a
+                // click always finds it.
+                let ticket = (!data.actions.is_empty()).then(|| crate::actions::register(&id));
                 crate::stats::record_shown(&stats_state, &ev, &src, remote);
+                if let Some(scope) = &dedupe {
+                    RECENT_DEDUPE_KEYS.record(scope, Instant::now());
+                }
                 // Explicitly set position with Logical coordinates (builder may use Physical)
                 let _ =
                     win.set_position(tauri::Position::Logical(tauri::LogicalPosition::new(x, y)));
 
                 // 알림 소리 재생
-                if crate::setup::load_notification_sound() {
+                if should_play_sound(priority, crate::setup::load_notification_sound()) {
                     crate::sound::play_notification_sound();
                 }
                 // Also emit event as backup (frontend primarily uses invoke)
@@ -348,6 +623,7 @@ pub fn show_notification(
                         }
                     }
                 });
+                NotifyOutcome::Accepted { ticket }
             }
             Err(e) => {
                 log::debug!("[NOTIFY] Window creation FAILED: id={}, err={}", id, e);
@@ -355,6 +631,9 @@ pub fn show_notification(
                 let mut mgr = state.lock().unwrap();
                 mgr.notifications.retain(|n| n.id != id);
                 mgr.heights.remove(&id);
+                NotifyOutcome::Dropped {
+                    reason: DropReason::Failed,
+                }
             }
         }
     }
@@ -375,6 +654,10 @@ pub fn close_notification(
     let heights = mgr.heights.clone();
     drop(mgr);
 
+    // An action click settles its ticket before closing; every other close
+    // reports the toast as dismissed to a waiting sender.
+    crate::actions::settle(id, ActionResult::Dismissed);
+
     // Record exactly once per toast: only the first close (the one that actually
     // removed the entry) sees `found`. A racing second close finds nothing.
     if let Some(nd) = found {
@@ -636,6 +919,10 @@ mod tests {
             source: String::new(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         }
     }
 
@@ -647,24 +934,114 @@ mod tests {
         }
     }
 
+    // ── extra_rows tests ──
+
+    #[test]
+    fn extra_rows_caps_count_and_value_length() {
+        let extra: BTreeMap<String, String> = (0..MAX_EXTRA_FIELDS + 2)
+            .map(|i| (format!("k{i:02}"), "v".repeat(MAX_EXTRA_VALUE_CHARS + 5)))
+            .collect();
+        let rows = extra_rows(extra);
+        assert_eq!(rows.len(), MAX_EXTRA_FIELDS);
+        assert_eq!(rows[0].0, "k00", "키 순서 유지");
+        assert!(rows[0].1.chars().count() <= MAX_EXTRA_VALUE_CHARS);
+    }
+
+    #[test]
+    fn extra_rows_drops_blank_keys() {
+        let extra = BTreeMap::from([
+            (" ".to_string(), "x".to_string()),
+            ("branch".to_string(), "main".to_string()),
+        ]);
+        assert_eq!(extra_rows(extra), vec![("branch".into(), "main".into())]);
+    }
+
     // ── resolve_auto_dismiss tests ──
 
     #[test]
     fn resolve_auto_dismiss_update_available_is_sticky() {
         // 업데이트 가능 알림은 설정과 무관하게 0(sticky)
-        assert_eq!(resolve_auto_dismiss("updater", "update_available", 10), 0);
+        assert_eq!(
+            resolve_auto_dismiss("updater", "update_available", false, Priority::Normal, 10),
+            0
+        );
     }
 
     #[test]
     fn resolve_auto_dismiss_update_completed_uses_config() {
         // 설치 완료 알림(task_complete)은 설정값 그대로
-        assert_eq!(resolve_auto_dismiss("updater", "task_complete", 10), 10);
+        assert_eq!(
+            resolve_auto_dismiss("updater", "task_complete", false, Priority::Normal, 10),
+            10
+        );
     }
 
     #[test]
     fn resolve_auto_dismiss_normal_uses_config() {
-        assert_eq!(resolve_auto_dismiss("claude", "task_complete", 7), 7);
-        assert_eq!(resolve_auto_dismiss("claude", "user_input_required", 0), 0);
+        assert_eq!(
+            resolve_auto_dismiss("claude", "task_complete", false, Priority::Normal, 7),
+            7
+        );
+        assert_eq!(
+            resolve_auto_dismiss("claude", "user_input_required", false, Priority::Normal, 0),
+            0
+        );
+    }
+
+    #[test]
+    fn resolve_auto_dismiss_with_actions_is_sticky() {
+        assert_eq!(
+            resolve_auto_dismiss("claude", "user_input_required", true, Priority::Normal, 10),
+            0
+        );
+    }
+
+    #[test]
+    fn resolve_auto_dismiss_scales_with_priority() {
+        let r = |p, c| resolve_auto_dismiss("claude", "task_complete", false, p, c);
+        assert_eq!(r(Priority::Critical, 10), 0, "critical은 sticky");
+        assert_eq!(r(Priority::High, 10), 20);
+        assert_eq!(r(Priority::High, 0), 0, "자동 닫힘 꺼짐은 그대로");
+        assert_eq!(r(Priority::Low, 10), LOW_PRIORITY_DISMISS_SECONDS);
+        assert_eq!(r(Priority::Low, 3), 3);
+        assert_eq!(r(Priority::Low, 0), LOW_PRIORITY_DISMISS_SECONDS);
+    }
+
+    #[test]
+    fn sound_follows_priority() {
+        assert!(!should_play_sound(Priority::Low, true));
+        assert!(should_play_sound(Priority::Normal, true));
+        assert!(!should_play_sound(Priority::High, false));
+        assert!(should_play_sound(Priority::Critical, false));
+    }
+
+    #[test]
+    fn rate_limiter_only_drops_below_high() {
+        assert!(rate_limit_may_drop(Priority::Low));
+        assert!(rate_limit_may_drop(Priority::Normal));
+        assert!(!rate_limit_may_drop(Priority::High));
+        assert!(!rate_limit_may_drop(Priority::Critical));
+    }
+
+    #[test]
+    fn focused_skip_exempts_critical_only() {
+        assert!(skip_when_focused(Priority::High));
+        assert!(!skip_when_focused(Priority::Critical));
+    }
+
+    #[test]
+    fn batch_larger_than_burst_is_capped_at_burst() {
+        // 배치 항목도 하나씩 토큰을 소비하므로 burst(10)를 넘겨 표시하지 않음
+        let limiter = RateLimiter::new(0, 10);
+        let shown = (0..agent_toast_core::wire::MAX_BATCH_ITEMS)
+            .filter(|_| rate_admit(&limiter, Priority::Normal))
+            .count();
+        assert_eq!(shown, 10);
+        assert!(
+            rate_admit(&limiter, Priority::High),
+            "high는 버킷이 비어도 표시"
+        );
+        assert!(!rate_admit(&limiter, Priority::Low));
     }
 
     // ── has_active_update_toast tests ──
@@ -837,6 +1214,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -863,6 +1244,10 @@ mod tests {
             source: "codex".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -883,6 +1268,10 @@ mod tests {
             source: "updater".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         assert!(data.process_tree.is_empty());
     }
@@ -900,6 +1289,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -955,6 +1348,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
         }
         let result = get_notification_for_window(&state, "notify-1");
@@ -980,6 +1377,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
             mgr.notifications.push(NotificationData {
                 id: "notify-2".to_string(),
@@ -992,6 +1393,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
         }
 
@@ -1049,6 +1454,10 @@ mod tests {
                 source: source.to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             };
             assert_eq!(data.source, source);
         }
@@ -1068,6 +1477,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             };
             assert_eq!(data.auto_dismiss_seconds, seconds);
         }
@@ -1086,6 +1499,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let cloned = data.clone();
         assert_eq!(cloned.id, data.id);
@@ -1110,6 +1527,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
 
         {
@@ -1143,6 +1564,10 @@ mod tests {
                     source: "claude".to_string(),
                     hostname: None,
                     show_hostname: false,
+                    actions: vec![],
+                    session_id: None,
+                    cwd: None,
+                    extra: vec![],
                 });
             }
             assert_eq!(mgr.notifications.len(), 5);
@@ -1199,6 +1624,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
         }
 
@@ -1222,6 +1651,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let debug = format!("{:?}", data);
         assert!(debug.contains("test"));
@@ -1272,6 +1705,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1292,6 +1729,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1313,6 +1754,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1332,6 +1777,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1351,6 +1800,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1370,6 +1823,10 @@ mod tests {
             source: "".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1409,6 +1866,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
         }
         // 빈 ID로도 조회 가능
@@ -1432,6 +1893,10 @@ mod tests {
                 source: "claude".to_string(),
                 hostname: None,
                 show_hostname: false,
+                actions: vec![],
+                session_id: None,
+                cwd: None,
+                extra: vec![],
             });
             // 존재하지 않는 ID로 retain → 변화 없음
             mgr.notifications.retain(|n| n.id != "nonexistent");
@@ -1456,6 +1921,10 @@ mod tests {
                     source: "claude".to_string(),
                     hostname: None,
                     show_hostname: false,
+                    actions: vec![],
+                    session_id: None,
+                    cwd: None,
+                    extra: vec![],
                 });
             }
             // source_hwnd 기준 필터 (모든 항목이 100)
@@ -1482,6 +1951,10 @@ mod tests {
             source: "claude".to_string(),
             hostname: None,
             show_hostname: false,
+            actions: vec![],
+            session_id: None,
+            cwd: None,
+            extra: vec![],
         };
         let json = serde_json::to_string(&data).unwrap();
         let deserialized: NotificationData = serde_json::from_str(&json).unwrap();
@@ -1523,4 +1996,120 @@ mod rate_limit_tests {
         }
         assert!(!bucket.try_consume());
     }
+
+    #[test]
+    fn dedupe_cache_drops_repeat_within_window() {
+        let cache = DedupeCache::new();
+        let t0 = Instant::now();
+        let window = Duration::from_secs(10);
+        assert!(!cache.is_recent("k", window, t0));
+        cache.record("k", t0);
+        assert!(cache.is_recent("k", window, t0 + Duration::from_secs(9)));
+        assert!(!cache.is_recent("other", window, t0));
+    }
+
+    #[test]
+    fn dedupe_cache_expires_from_first_showing() {
+        let cache = DedupeCache::new();
+        let t0 = Instant::now();
+        let window = Duration::from_secs(10);
+        cache.record("k", t0);
+        // 창 안의 재시도가 만료 시점을 늦추지 않아야 함
+        assert!(cache.is_recent("k", window, t0 + Duration::from_secs(5)));
+        assert!(!cache.is_recent("k", window, t0 + Duration::from_secs(10)));
+    }
+
+    fn keyed_request(source: &str, hostname: Option<&str>, key: Option<&str>) -> NotifyRequest {
+        serde_json::from_value(serde_json::json!({
+            "pid": 1,
+            "event": "task_complete",
+            "source": source,
+            "hostname": hostname,
+            "dedupe_key": key,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn rate_limited_request_is_not_a_duplicate_on_retry() {
+        let cache = DedupeCache::new();
+        let req = keyed_request("claude", None, Some("k"));
+        let t0 = Instant::now();
+        // 첫 시도는 토큰 부족으로 드롭 — 키가 기록되면 안 됨
+        let empty = RateLimiter::new(0, 0);
+        assert_eq!(
+            admit(&req, &cache, 10, Some(&empty), t0),
+            Err(DropReason::RateLimited)
+        );
+        // 창 안의 재시도는 표시되고, 표시된 뒤에야 키가 기록됨
+        let full = RateLimiter::new(0, 10);
+        let scope = admit(&req, &cache, 10, Some(&full), t0 + Duration::from_secs(1))
+            .unwrap()
+            .expect("keyed request has a scope");
+        cache.record(&scope, t0 + Duration::from_secs(1));
+        assert_eq!(
+            admit(&req, &cache, 10, Some(&full), t0 + Duration::from_secs(2)),
+            Err(DropReason::Duplicate)
+        );
+    }
+
+    #[test]
+    fn admit_without_window_or_limiter() {
+        let cache = DedupeCache::new();
+        let req = keyed_request("claude", None, Some("k"));
+        let now = Instant::now();
+        // window 0 = 중복 억제 꺼짐, limiter 없음 = 배치 요약 알림
+        assert_eq!(admit(&req, &cache, 0, None, now), Ok(None));
+        let empty = RateLimiter::new(0, 0);
+        assert!(admit(&req, &cache, 10, None, now).is_ok());
+        assert!(admit(&req, &cache, 10, Some(&empty), now).is_err());
+    }
+
+    #[test]
+    fn overflow_summary_counts_events() {
+        let mut overflow: Vec<NotifyRequest> = (0..85)
+            .map(|i| {
+                let mut r = keyed_request("claude", Some("vps"), Some(&format!("k{i}")));
+                if i % 5 == 0 {
+                    r.event = "error".into();
+                }
+                r
+            })
+            .collect();
+        overflow[1].priority = Priority::Low;
+        let summary = overflow_summary(&overflow, "en");
+        assert_eq!(summary.message.as_deref(), Some("85 more notifications"));
+        assert_eq!(summary.extra.get("error").map(String::as_str), Some("17"));
+        assert_eq!(
+            summary.extra.get("task_complete").map(String::as_str),
+            Some("68")
+        );
+        assert_eq!(summary.priority, Priority::Normal);
+        assert_eq!(summary.hostname.as_deref(), Some("vps"));
+        assert!(summary.dedupe_key.is_none(), "요약은 중복 억제 대상이 아님");
+        assert!(overflow_summary(&overflow[..1], "ko")
+            .message
+            .unwrap()
+            .contains('1'));
+    }
+
+    #[test]
+    fn dedupe_scope_none_without_key() {
+        assert!(dedupe_scope(&keyed_request("claude", None, None)).is_none());
+        assert!(dedupe_scope(&keyed_request("claude", None, Some(""))).is_none());
+    }
+
+    #[test]
+    fn dedupe_scope_separates_hosts_and_sources() {
+        let local = dedupe_scope(&keyed_request("claude", None, Some("k")));
+        let remote = dedupe_scope(&keyed_request("claude", Some("vps"), Some("k")));
+        let codex = dedupe_scope(&keyed_request("codex", None, Some("k")));
+        assert!(local.is_some());
+        assert_ne!(local, remote);
+        assert_ne!(local, codex);
+        assert_eq!(
+            local,
+            dedupe_scope(&keyed_request("claude", None, Some("k")))
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::cli::NotifyRequest;
-use std::io::Write;
+use agent_toast_core::frame;
+use agent_toast_core::wire::{ActionPoll, ActionResult, NotifyOutcome, NotifyReply};
 
 #[cfg(debug_assertions)]
 const PIPE_NAME: &str = r"\\.\pipe\agent-toast-dev";
@@ -13,26 +14,107 @@ pub fn is_server_running() -> bool {
     OpenOptions::new().write(true).open(PIPE_NAME).is_ok()
 }
 
+/// Fire-and-forget send. Does not wait for the server's reply frame.
 pub fn try_send(request: &NotifyRequest) -> Result<bool, Box<dyn std::error::Error>> {
     use std::fs::OpenOptions;
 
     let file = OpenOptions::new().write(true).open(PIPE_NAME);
     match file {
         Ok(mut f) => {
-            let data = serde_json::to_vec(request)?;
-            let len = (data.len() as u32).to_le_bytes();
-            f.write_all(&len)?;
-            f.write_all(&data)?;
-            f.flush()?;
+            frame::write_json(&mut f, request)?;
             Ok(true)
         }
         Err(_) => Ok(false),
     }
 }
 
+/// Send and read the server's [`NotifyReply`]. `Ok(None)` when no server is
+/// running. A server that predates reply frames just hangs up, which reads
+/// as a reply without a ticket.
+pub fn send_with_reply(
+    request: &NotifyRequest,
+) -> Result<Option<NotifyReply>, Box<dyn std::error::Error>> {
+    let data = serde_json::to_vec(request)?;
+    let Some(reply) = exchange(&data)? else {
+        return Ok(None);
+    };
+    Ok(Some(serde_json::from_slice(&reply).unwrap_or_default()))
+}
+
+/// Ask the running instance for the current result of an action ticket.
+pub fn poll_action(ticket: &str) -> Result<ActionResult, Box<dyn std::error::Error>> {
+    let data = serde_json::to_vec(&ActionPoll {
+        poll: ticket.to_string(),
+    })?;
+    match exchange(&data)? {
+        Some(reply) => Ok(serde_json::from_slice(&reply)?),
+        None => Ok(ActionResult::Unknown),
+    }
+}
+
+/// Poll `ticket` until the toast settles or `deadline` passes. Returns the
+/// clicked action id; dismissals, expiry and pipe errors yield `None`.
+pub fn wait_for_action(ticket: &str, deadline: std::time::Instant) -> Option<String> {
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+    loop {
+        match poll_action(ticket).unwrap_or(ActionResult::Unknown) {
+            ActionResult::Clicked { action } => return Some(action),
+            ActionResult::Pending if std::time::Instant::now() + POLL_INTERVAL < deadline => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// One request/response round trip. `Ok(None)` if the pipe can't be opened;
+/// `Ok(Some(empty))` if the server hung up without replying.
+fn exchange(data: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    use std::fs::OpenOptions;
+
+    let Ok(mut f) = OpenOptions::new().read(true).write(true).open(PIPE_NAME) else {
+        return Ok(None);
+    };
+    frame::write_frame(&mut f, data)?;
+    Ok(Some(
+        frame::read_frame(&mut f).ok().flatten().unwrap_or_default(),
+    ))
+}
+
+/// Decode one incoming frame and produce the reply frame payload: a poll frame
+/// answers with the ticket's [`ActionResult`], a notification with a
+/// [`NotifyReply`]. `None` for frames that are neither.
+fn handle_frame<F>(buf: &[u8], on_request: &F) -> Option<Vec<u8>>
+where
+    F: Fn(NotifyRequest) -> NotifyOutcome,
+{
+    if let Ok(poll) = serde_json::from_slice::<ActionPoll>(buf) {
+        return serde_json::to_vec(&crate::actions::poll(&poll.poll)).ok();
+    }
+    match serde_json::from_slice::<NotifyRequest>(buf) {
+        Ok(req) => {
+            log::debug!(
+                "[PIPE] Received request: event={}, pid={}",
+                req.event,
+                req.pid
+            );
+            let ticket = match on_request(req) {
+                NotifyOutcome::Accepted { ticket } => ticket,
+                _ => None,
+            };
+            log::debug!("[PIPE] Callback completed");
+            serde_json::to_vec(&NotifyReply { ticket }).ok()
+        }
+        Err(e) => {
+            log::error!("[PIPE] JSON parse error: {}", e);
+            None
+        }
+    }
+}
+
 pub fn start_server<F>(on_request: F)
 where
-    F: Fn(NotifyRequest) + Send + 'static,
+    F: Fn(NotifyRequest) -> NotifyOutcome + Send + 'static,
 {
     std::thread::spawn(move || {
         let mut fail_count: u32 = 0;
@@ -52,11 +134,11 @@ where
 #[cfg(windows)]
 fn run_pipe_instance<F>(on_request: &F) -> Result<(), Box<dyn std::error::Error>>
 where
-    F: Fn(NotifyRequest),
+    F: Fn(NotifyRequest) -> NotifyOutcome,
 {
     use windows::core::HSTRING;
     use windows::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
-    use windows::Win32::Storage::FileSystem::{ReadFile, PIPE_ACCESS_INBOUND};
+    use windows::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
     use windows::Win32::System::Pipes::{
         ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
         PIPE_TYPE_BYTE, PIPE_WAIT,
@@ -66,7 +148,7 @@ where
     let handle: HANDLE = unsafe {
         CreateNamedPipeW(
             &pipe_name,
-            PIPE_ACCESS_INBOUND,
+            PIPE_ACCESS_DUPLEX,
             PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
             255,
             4096,
@@ -87,48 +169,19 @@ where
         .map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?;
     log::debug!("[PIPE] Client connected");
 
-    // Read length prefix
-    let mut len_buf = [0u8; 4];
-    let mut bytes_read = 0u32;
-    if let Err(e) = unsafe { ReadFile(handle, Some(&mut len_buf), Some(&mut bytes_read), None) } {
-        // Broken pipe = client connected and immediately disconnected
-        // (e.g., is_server_running() probe). Not a real error.
-        let is_broken_pipe = e.code() == windows::Win32::Foundation::ERROR_BROKEN_PIPE.to_hresult();
-        unsafe {
-            let _ = DisconnectNamedPipe(handle);
-            let _ = CloseHandle(handle);
-        }
-        if is_broken_pipe {
-            log::debug!("[PIPE] Client disconnected without sending data");
-            return Ok(());
-        }
-        return Err(Box::new(e));
-    }
-    let len = u32::from_le_bytes(len_buf) as usize;
-
-    // Read payload
-    let mut buf = vec![0u8; len];
-    let mut total_read = 0usize;
-    while total_read < len {
-        let mut br = 0u32;
-        unsafe { ReadFile(handle, Some(&mut buf[total_read..]), Some(&mut br), None) }
-            .map_err(|e| -> Box<dyn std::error::Error> { Box::new(e) })?;
-        total_read += br as usize;
-    }
-
-    match serde_json::from_slice::<NotifyRequest>(&buf) {
-        Ok(req) => {
-            log::debug!(
-                "[PIPE] Received request: event={}, pid={}",
-                req.event,
-                req.pid
-            );
-            on_request(req);
-            log::debug!("[PIPE] Callback completed");
-        }
-        Err(e) => {
-            log::error!("[PIPE] JSON parse error: {}", e);
+    // A client that connects and hangs up without sending (the
+    // is_server_running() probe) reads as `None`, which is not an error.
+    let mut conn = PipeConnection(handle);
+    match frame::read_frame(&mut conn) {
+        Ok(Some(buf)) => {
+            // Reply on the same connection. Fire-and-forget clients have
+            // usually hung up by now, so write errors are expected and ignored.
+            if let Some(reply) = handle_frame(&buf, on_request) {
+                let _ = frame::write_frame(&mut conn, &reply);
+            }
         }
+        Ok(None) => log::debug!("[PIPE] Client disconnected without sending data"),
+        Err(e) => log::error!("[PIPE] Frame read error: {e}"),
     }
 
     unsafe {
@@ -143,10 +196,49 @@ where
     Ok(())
 }
 
+/// Blocking `Read`/`Write` over a connected pipe handle so the server can use
+/// the shared [`frame`] codec.
+#[cfg(windows)]
+struct PipeConnection(windows::Win32::Foundation::HANDLE);
+
+#[cfg(windows)]
+impl std::io::Read for PipeConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use windows::Win32::Storage::FileSystem::ReadFile;
+        let mut read = 0u32;
+        match unsafe { ReadFile(self.0, Some(buf), Some(&mut read), None) } {
+            Ok(()) => Ok(read as usize),
+            // The client closed its end: that's EOF for a byte stream.
+            Err(e) if e.code() == windows::Win32::Foundation::ERROR_BROKEN_PIPE.to_hresult() => {
+                Ok(0)
+            }
+            Err(e) => Err(std::io::Error::other(e)),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl std::io::Write for PipeConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        use windows::Win32::Storage::FileSystem::WriteFile;
+        let mut written = 0u32;
+        unsafe { WriteFile(self.0, Some(buf), Some(&mut written), None) }
+            .map_err(std::io::Error::other)?;
+        Ok(written as usize)
+    }
+
+    /// Waits for the client to read the reply before DisconnectNamedPipe
+    /// discards it.
+    fn flush(&mut self) -> std::io::Result<()> {
+        use windows::Win32::Storage::FileSystem::FlushFileBuffers;
+        unsafe { FlushFileBuffers(self.0) }.map_err(std::io::Error::other)
+    }
+}
+
 #[cfg(not(windows))]
 fn run_pipe_instance<F>(_on_request: &F) -> Result<(), Box<dyn std::error::Error>>
 where
-    F: Fn(NotifyRequest),
+    F: Fn(NotifyRequest) -> NotifyOutcome,
 {
     Err("Named pipes are only supported on Windows".into())
 }
@@ -154,6 +246,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use agent_toast_core::wire::{HookContext, Priority, SCHEMA_VERSION};
 
     /// try_send가 사용하는 와이어 포맷 검증: [4바이트 LE 길이][JSON 페이로드]
     /// 실제 수신 측 디코딩 흐름을 시뮬레이션하여 프레임 단위로 검증
@@ -167,21 +260,25 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
-        let len_bytes = (data.len() as u32).to_le_bytes();
 
-        // 프레임 조립: [4바이트 LE 길이][JSON 페이로드]
-        let mut frame = Vec::new();
-        frame.extend_from_slice(&len_bytes);
-        frame.extend_from_slice(&data);
+        // 프레임: [4바이트 LE 길이][JSON 페이로드]
+        let bytes = frame::encode(&data).unwrap();
+        assert_eq!(bytes.len(), 4 + data.len());
+        assert_eq!(&bytes[..4], (data.len() as u32).to_le_bytes());
 
-        // 수신 측 디코딩 시뮬레이션
-        assert_eq!(frame.len(), 4 + data.len());
-        let received_len = u32::from_le_bytes(frame[0..4].try_into().unwrap()) as usize;
-        assert_eq!(received_len, data.len());
-        let decoded: NotifyRequest = serde_json::from_slice(&frame[4..4 + received_len]).unwrap();
+        // 수신 측 디코딩
+        let decoded: NotifyRequest = frame::read_json(&mut std::io::Cursor::new(bytes))
+            .unwrap()
+            .unwrap();
         assert_eq!(decoded.pid, 1234);
         assert_eq!(decoded.event, "task_complete");
         assert_eq!(decoded.message.as_deref(), Some("빌드 완료"));
@@ -197,20 +294,21 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
-        let data = serde_json::to_vec(&req).unwrap();
-        let len = (data.len() as u32).to_le_bytes();
-
-        // 프레임 조립
-        let mut frame = Vec::new();
-        frame.extend_from_slice(&len);
-        frame.extend_from_slice(&data);
+        let mut buf = Vec::new();
+        frame::write_json(&mut buf, &req).unwrap();
 
-        // 수신 측 디코딩 시뮬레이션
-        let received_len = u32::from_le_bytes(frame[0..4].try_into().unwrap()) as usize;
-        let received_data = &frame[4..4 + received_len];
-        let decoded: NotifyRequest = serde_json::from_slice(received_data).unwrap();
+        // 수신 측 디코딩
+        let decoded: NotifyRequest = frame::read_json(&mut std::io::Cursor::new(buf))
+            .unwrap()
+            .unwrap();
         assert_eq!(decoded.pid, 1);
         assert_eq!(decoded.event, "error");
         assert!(decoded.message.is_none());
@@ -226,6 +324,12 @@ mod tests {
             process_tree: Some(vec![100, 200, 300, 400]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -245,6 +349,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -271,6 +381,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -293,6 +409,12 @@ mod tests {
                 process_tree: None,
                 source: source.into(),
                 hostname: None,
+                schema_version: SCHEMA_VERSION,
+                dedupe_key: None,
+                actions: vec![],
+                priority: Priority::Normal,
+                context: HookContext::default(),
+                extra: Default::default(),
             };
 
             let data = serde_json::to_vec(&req).unwrap();
@@ -311,19 +433,21 @@ mod tests {
             process_tree: Some(vec![1000, 2000, 3000, 4000, 5000]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
-        let data = serde_json::to_vec(&req).unwrap();
-        let len_bytes = (data.len() as u32).to_le_bytes();
-
-        // 프레임 조립
-        let mut frame = Vec::new();
-        frame.extend_from_slice(&len_bytes);
-        frame.extend_from_slice(&data);
+        let mut buf = Vec::new();
+        frame::write_json(&mut buf, &req).unwrap();
 
         // 수신 측 디코딩
-        let received_len = u32::from_le_bytes(frame[0..4].try_into().unwrap()) as usize;
-        let decoded: NotifyRequest = serde_json::from_slice(&frame[4..4 + received_len]).unwrap();
+        let decoded: NotifyRequest = frame::read_json(&mut std::io::Cursor::new(buf))
+            .unwrap()
+            .unwrap();
 
         assert_eq!(decoded.pid, 99999);
         assert_eq!(decoded.event, "user_input_required");
@@ -346,6 +470,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -364,6 +494,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -382,6 +518,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -407,6 +549,12 @@ mod tests {
             process_tree: Some(big_tree),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data = serde_json::to_vec(&req).unwrap();
@@ -501,6 +649,12 @@ mod tests {
             process_tree: Some(vec![1, 2, 3]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let len = (data.len() as u32).to_le_bytes();
@@ -543,6 +697,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -559,6 +719,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -575,6 +741,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -591,6 +763,12 @@ mod tests {
             process_tree: None,
             source: "".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -607,6 +785,12 @@ mod tests {
             process_tree: Some(vec![42]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -624,6 +808,12 @@ mod tests {
             process_tree: Some(vec![]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let req_none = NotifyRequest {
             pid: 1,
@@ -633,6 +823,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
 
         let data_empty = serde_json::to_vec(&req_empty).unwrap();
@@ -655,6 +851,12 @@ mod tests {
             process_tree: Some(vec![0, 0, 0]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -671,6 +873,12 @@ mod tests {
             process_tree: Some(vec![u32::MAX]),
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -688,6 +896,12 @@ mod tests {
             process_tree: None,
             source: "".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let len = data.len() as u32;
@@ -707,6 +921,12 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
@@ -724,9 +944,72 @@ mod tests {
             process_tree: None,
             source: "claude".into(),
             hostname: None,
+            schema_version: SCHEMA_VERSION,
+            dedupe_key: None,
+            actions: vec![],
+            priority: Priority::Normal,
+            context: HookContext::default(),
+            extra: Default::default(),
         };
         let data = serde_json::to_vec(&req).unwrap();
         let decoded: NotifyRequest = serde_json::from_slice(&data).unwrap();
         assert_eq!(decoded.title_hint.as_deref(), Some(long_hint.as_str()));
     }
+
+    #[test]
+    fn try_send_frame_decodes_on_server_side() {
+        // 클라이언트가 쓰는 프레임을 서버 쪽 handle_frame까지 그대로 통과
+        let mut buf = Vec::new();
+        frame::write_json(
+            &mut buf,
+            &serde_json::json!({"pid": 3, "event": "task_complete"}),
+        )
+        .unwrap();
+        let body = frame::read_frame(&mut std::io::Cursor::new(buf))
+            .unwrap()
+            .unwrap();
+        let reply = handle_frame(&body, &|req: NotifyRequest| {
+            assert_eq!(req.pid, 3);
+            NotifyOutcome::Dropped {
+                reason: agent_toast_core::wire::DropReason::Focused,
+            }
+        })
+        .unwrap();
+        let reply: NotifyReply = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(reply.ticket, None);
+    }
+
+    #[test]
+    fn handle_frame_replies_with_ticket() {
+        let body = br#"{"pid":1,"event":"user_input_required","actions":[{"id":"a","label":"A"}]}"#;
+        let reply = handle_frame(body, &|req: NotifyRequest| {
+            assert_eq!(req.actions.len(), 1);
+            NotifyOutcome::Accepted {
+                ticket: Some("t-9".to_string()),
+            }
+        })
+        .unwrap();
+        let reply: NotifyReply = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(reply.ticket.as_deref(), Some("t-9"));
+    }
+
+    #[test]
+    fn handle_frame_answers_poll_without_calling_back() {
+        let ticket = crate::actions::register("notify-pipe-poll");
+        let body = serde_json::to_vec(&ActionPoll { poll: ticket }).unwrap();
+        let reply = handle_frame(&body, &|_req: NotifyRequest| -> NotifyOutcome {
+            panic!("poll frame must not be treated as a notification")
+        })
+        .unwrap();
+        let result: ActionResult = serde_json::from_slice(&reply).unwrap();
+        assert_eq!(result, ActionResult::Pending);
+    }
+
+    #[test]
+    fn handle_frame_ignores_garbage() {
+        assert!(handle_frame(b"not json", &|_req: NotifyRequest| {
+            NotifyOutcome::Accepted { ticket: None }
+        })
+        .is_none());
+    }
 }
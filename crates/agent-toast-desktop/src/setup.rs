@@ -1,6 +1,14 @@
-use agent_toast_core::hook_config::{is_agent_toast_cmd, merge_agent_toast_hooks, HookEntry};
+use agent_toast_core::backup;
+use agent_toast_core::codex_config;
+use agent_toast_core::hook_config::{
+    diff_hooks, duplicate_hooks, event_for, is_agent_toast_cmd, merge_agent_toast_hooks,
+    remove_agent_toast_hooks, waiting_timeout, DuplicateHook, HookDiff, HookEntry, Scope,
+    DEFAULT_TIMEOUT_SECS,
+};
+use agent_toast_core::json_edit::rewrite;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 /// Hook configuration as shown in the setup GUI
@@ -52,6 +60,9 @@ pub struct HookConfig {
     /// 알림 자동 소멸 시간 (초). 0이면 자동 소멸 안 함.
     #[serde(default = "default_auto_dismiss_seconds")]
     pub auto_dismiss_seconds: u32,
+    /// 같은 dedupe_key 알림을 무시하는 시간 (초). 0이면 중복 제거 안 함.
+    #[serde(default = "default_dedupe_window_seconds")]
+    pub dedupe_window_seconds: u32,
     /// 알림 표시 위치: "bottom_right", "bottom_left", "top_right", "top_left"
     #[serde(default = "default_notification_position")]
     pub notification_position: String,
@@ -93,6 +104,17 @@ pub struct HookConfig {
     /// fallback 으로 동작한다.
     #[serde(default = "default_dynamic_message_enabled")]
     pub dynamic_message_enabled: bool,
+    /// PermissionRequest 알림에 허용/거부 버튼을 달고 클릭 결과를 Claude Code에 응답.
+    /// 켜지면 PermissionRequest 훅 커맨드에 `--decide` 가 붙는다.
+    #[serde(default)]
+    pub permission_decide_enabled: bool,
+    /// 훅 커맨드의 `timeout`(초). 데스크톱이 응답하지 않아도 Claude Code가 기본
+    /// 타임아웃만큼 멈추지 않게 한다. 0 = Claude 기본값. `--decide` 훅은 대기 시간이 더해진다.
+    #[serde(default = "default_hook_timeout_seconds")]
+    pub hook_timeout_seconds: u32,
+    /// 알림 훅을 백그라운드로 실행 (`"async": true`). `--decide` 훅은 제외.
+    #[serde(default)]
+    pub hook_async: bool,
     /// 알림 토스트 액센트 바: "left" | "none"
     #[serde(default = "default_toast_bar")]
     pub toast_bar: String,
@@ -114,6 +136,25 @@ pub struct HookConfig {
     /// 알림 토스트 코드 폰트 패밀리명. "" = 기본(번들 D2Coding)
     #[serde(default)]
     pub toast_font_mono: String,
+    /// 훅 항목별 `--template` (키: "stop", "permission_request" 등 `_message` 접두어).
+    /// 비어 있으면 템플릿 없이 기존 메시지 결정 순서를 따른다.
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+    /// 훅을 등록할 settings.json: "user"(기본, ~/.claude) | "project" | "local".
+    /// 앱 설정(`agent_toast` 블록)은 범위와 상관없이 항상 user 파일에 저장.
+    #[serde(default = "default_hook_scope")]
+    pub hook_scope: String,
+    /// project / local 범위의 프로젝트 루트 폴더
+    #[serde(default)]
+    pub project_dir: String,
+}
+
+fn default_hook_timeout_seconds() -> u32 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+fn default_hook_scope() -> String {
+    Scope::User.as_str().into()
 }
 
 fn default_title_display_mode() -> String {
@@ -128,6 +169,10 @@ fn default_auto_dismiss_seconds() -> u32 {
     0
 }
 
+fn default_dedupe_window_seconds() -> u32 {
+    30
+}
+
 fn default_notification_position() -> String {
     "bottom_right".into()
 }
@@ -324,6 +369,7 @@ impl Default for HookConfig {
             title_display_mode: "project".into(),
             auto_close_on_focus: true,
             auto_dismiss_seconds: 0,
+            dedupe_window_seconds: default_dedupe_window_seconds(),
             http_enabled: false,
             http_port: default_http_port(),
             show_hostname: true,
@@ -337,6 +383,9 @@ impl Default for HookConfig {
             version: String::new(),
             theme: default_theme(),
             dynamic_message_enabled: default_dynamic_message_enabled(),
+            permission_decide_enabled: false,
+            hook_timeout_seconds: default_hook_timeout_seconds(),
+            hook_async: false,
             toast_bar: default_toast_bar(),
             toast_border: default_toast_border(),
             toast_effects: Vec::new(),
@@ -344,15 +393,25 @@ impl Default for HookConfig {
             toast_density: default_toast_density(),
             toast_font_sans: String::new(),
             toast_font_mono: String::new(),
+            templates: BTreeMap::new(),
+            hook_scope: default_hook_scope(),
+            project_dir: String::new(),
         }
     }
 }
 
+fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
 fn settings_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".claude")
-        .join("settings.json")
+    home_dir().join(".claude").join("settings.json")
+}
+
+/// Write a hook scope's settings file or the Codex config, keeping a backup
+/// of the previous version under ~/.claude (see [`backup`]).
+fn write_with_backup(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    backup::write_with_backup(&home_dir(), path, contents)
 }
 
 /// 커스텀 알림 소리 복사본 보관 폴더 (%LOCALAPPDATA%\agent-toast\sounds)
@@ -412,14 +471,29 @@ fn exe_path_for_shell() -> String {
     format!("\"{}\"", path)
 }
 
-/// Read current hook config from ~/.claude/settings.json
+/// Read current hook config from ~/.claude/settings.json. With a project or
+/// local scope, the enabled hooks come from that scope's file instead.
 #[tauri::command]
 pub fn get_hook_config() -> HookConfig {
     let path = settings_path();
     let Ok(content) = std::fs::read_to_string(&path) else {
         return HookConfig::default();
     };
-    parse_hook_config_from_json(&content)
+    let Ok(mut root) = serde_json::from_str::<Value>(&content) else {
+        return HookConfig::default();
+    };
+    let config = parse_hook_config_from_root(&root);
+    match hook_target(&config) {
+        Ok((Scope::User, _)) | Err(_) => config,
+        Ok((_, target)) => {
+            root["hooks"] = std::fs::read_to_string(target)
+                .ok()
+                .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+                .map(|scoped| scoped["hooks"].clone())
+                .unwrap_or(Value::Null);
+            parse_hook_config_from_root(&root)
+        }
+    }
 }
 
 /// Parse hook config from raw JSON string. Separated for testability.
@@ -427,7 +501,10 @@ fn parse_hook_config_from_json(content: &str) -> HookConfig {
     let Ok(root) = serde_json::from_str::<Value>(content) else {
         return HookConfig::default();
     };
+    parse_hook_config_from_root(&root)
+}
 
+fn parse_hook_config_from_root(root: &Value) -> HookConfig {
     let hooks = &root["hooks"];
 
     let mut config = HookConfig {
@@ -458,6 +535,10 @@ fn parse_hook_config_from_json(content: &str) -> HookConfig {
         auto_dismiss_seconds: root["agent_toast"]["auto_dismiss_seconds"]
             .as_u64()
             .unwrap_or(0) as u32,
+        dedupe_window_seconds: root["agent_toast"]["dedupe_window_seconds"]
+            .as_u64()
+            .map(|n| n as u32)
+            .unwrap_or_else(default_dedupe_window_seconds),
         notification_position: root["agent_toast"]["notification_position"]
             .as_str()
             .unwrap_or("bottom_right")
@@ -498,6 +579,14 @@ fn parse_hook_config_from_json(content: &str) -> HookConfig {
         dynamic_message_enabled: root["agent_toast"]["dynamic_message_enabled"]
             .as_bool()
             .unwrap_or_else(default_dynamic_message_enabled),
+        permission_decide_enabled: root["agent_toast"]["permission_decide_enabled"]
+            .as_bool()
+            .unwrap_or(false),
+        hook_timeout_seconds: root["agent_toast"]["hook_timeout_seconds"]
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .unwrap_or_else(default_hook_timeout_seconds),
+        hook_async: root["agent_toast"]["hook_async"].as_bool().unwrap_or(false),
         toast_bar: root["agent_toast"]["toast_bar"]
             .as_str()
             .unwrap_or("none")
@@ -530,6 +619,14 @@ fn parse_hook_config_from_json(content: &str) -> HookConfig {
             .as_str()
             .unwrap_or("")
             .to_string(),
+        hook_scope: root["agent_toast"]["hook_scope"]
+            .as_str()
+            .unwrap_or("user")
+            .to_string(),
+        project_dir: root["agent_toast"]["project_dir"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
         // 나머지는 Default에서 가져오기
         ..HookConfig::default()
     };
@@ -713,9 +810,49 @@ fn parse_hook_config_from_json(content: &str) -> HookConfig {
         }
     }
 
+    // --template은 이벤트와 무관하게 한 번에 수집
+    if let Some(events) = hooks.as_object() {
+        for (event_key, groups) in events {
+            for entry in groups.as_array().into_iter().flatten() {
+                let Some(cmd) = extract_agent_toast_cmd(entry) else {
+                    continue;
+                };
+                let matcher = entry["matcher"].as_str();
+                if let (Some(key), Some(tpl)) = (
+                    hook_item_key(event_key, matcher),
+                    extract_flag_value(cmd, "--template"),
+                ) {
+                    config.templates.insert(key.to_string(), tpl);
+                }
+            }
+        }
+    }
+
     config
 }
 
+/// 설정 화면의 훅 항목 키 (`<key>_enabled` / `<key>_message`의 접두어)
+fn hook_item_key(event_key: &str, matcher: Option<&str>) -> Option<&'static str> {
+    Some(match (event_key, matcher) {
+        ("Stop", _) => "stop",
+        ("Notification", Some("permission_prompt")) => "notification_permission",
+        ("Notification", Some("elicitation_dialog")) => "notification_elicitation",
+        ("Notification", Some("idle_prompt")) => "notification_idle",
+        ("SessionStart", _) => "session_start",
+        ("SessionEnd", _) => "session_end",
+        ("SubagentStart", _) => "subagent_start",
+        ("SubagentStop", _) => "subagent_stop",
+        ("PreCompact", _) => "pre_compact",
+        ("Setup", _) => "setup",
+        ("UserPromptSubmit", _) => "user_prompt_submit",
+        ("PreToolUse", _) => "pre_tool_use",
+        ("PostToolUse", _) => "post_tool_use",
+        ("PostToolUseFailure", _) => "post_tool_use_failure",
+        ("PermissionRequest", _) => "permission_request",
+        _ => return None,
+    })
+}
+
 /// Check if settings.json contains any agent-toast hooks
 #[tauri::command]
 pub fn is_hook_config_saved() -> bool {
@@ -778,6 +915,10 @@ fn write_agent_toast_settings(root: &mut Value, config: &HookConfig) {
         "auto_dismiss_seconds".into(),
         Value::Number(config.auto_dismiss_seconds.into()),
     );
+    cn.insert(
+        "dedupe_window_seconds".into(),
+        Value::Number(config.dedupe_window_seconds.into()),
+    );
     cn.insert(
         "notification_position".into(),
         Value::String(config.notification_position.clone()),
@@ -808,6 +949,15 @@ fn write_agent_toast_settings(root: &mut Value, config: &HookConfig) {
         "dynamic_message_enabled".into(),
         Value::Bool(config.dynamic_message_enabled),
     );
+    cn.insert(
+        "permission_decide_enabled".into(),
+        Value::Bool(config.permission_decide_enabled),
+    );
+    cn.insert(
+        "hook_timeout_seconds".into(),
+        Value::Number(config.hook_timeout_seconds.into()),
+    );
+    cn.insert("hook_async".into(), Value::Bool(config.hook_async));
     cn.insert("toast_bar".into(), Value::String(config.toast_bar.clone()));
     cn.insert(
         "toast_border".into(),
@@ -840,6 +990,14 @@ fn write_agent_toast_settings(root: &mut Value, config: &HookConfig) {
         "toast_font_mono".into(),
         Value::String(config.toast_font_mono.clone()),
     );
+    cn.insert(
+        "hook_scope".into(),
+        Value::String(config.hook_scope.clone()),
+    );
+    cn.insert(
+        "project_dir".into(),
+        Value::String(config.project_dir.clone()),
+    );
     cn.insert(
         "version".into(),
         Value::String(env!("CARGO_PKG_VERSION").to_string()),
@@ -847,6 +1005,25 @@ fn write_agent_toast_settings(root: &mut Value, config: &HookConfig) {
     root["agent_toast"] = Value::Object(cn);
 }
 
+/// Notification hook for `event_key`/`matcher`: `<exe> --event <event>
+/// --message "<message>"<extra>`, with the event from core's
+/// [`EVENT_MAP`](agent_toast_core::hook_config::EVENT_MAP).
+fn notify_entry(
+    exe: &str,
+    event_key: &'static str,
+    matcher: Option<&'static str>,
+    message: &str,
+    extra: &str,
+) -> HookEntry {
+    let event = event_for(event_key, matcher).unwrap_or("task_complete");
+    HookEntry {
+        event_key,
+        matcher,
+        command: format!("{exe} --event {event} --message \"{message}\"{extra}"),
+        ..HookEntry::default()
+    }
+}
+
 /// Append ` --dynamic` to every notification hook command when dynamic mode is
 /// enabled, leaving the infrastructure `--daemon` entry untouched (it carries no
 /// message). With `--dynamic` the CLI derives the toast body from the hook's
@@ -862,6 +1039,69 @@ fn apply_dynamic_flag(entries: &mut [HookEntry], enabled: bool) {
     }
 }
 
+/// Append ` --template "..."` to each notification command whose hook item
+/// has a non-empty template, quoted with [`shell_quote`]. The `--daemon`
+/// entry carries no message.
+fn apply_template_flags(entries: &mut [HookEntry], templates: &BTreeMap<String, String>) {
+    for e in entries.iter_mut() {
+        if e.command.contains("--daemon") {
+            continue;
+        }
+        let tpl = hook_item_key(e.event_key, e.matcher)
+            .and_then(|key| templates.get(key))
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty());
+        if let Some(tpl) = tpl {
+            e.command
+                .push_str(&format!(" --template {}", shell_quote(tpl)));
+        }
+    }
+}
+
+/// `value` double-quoted for the bash that runs hook commands: `\`, `"`,
+/// `$` and `` ` `` are backslash-escaped, everything else is literal.
+/// [`extract_flag_value`] undoes it.
+fn shell_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Append ` --decide` to the PermissionRequest command so the toast shows
+/// Allow/Deny buttons and the CLI prints Claude Code's hook decision.
+fn apply_decide_flag(entries: &mut [HookEntry], enabled: bool) {
+    if !enabled {
+        return;
+    }
+    for e in entries.iter_mut() {
+        if e.event_key == "PermissionRequest" {
+            e.command.push_str(" --decide");
+        }
+    }
+}
+
+/// Set every entry's hook `timeout` (`0` = Claude's default) and `async`
+/// option. A `--decide` command waits for a click and Claude reads its
+/// answer, so it gets the wait on top of the timeout and never runs async.
+fn apply_hook_options(entries: &mut [HookEntry], timeout: u32, run_async: bool) {
+    let timeout = (timeout > 0).then_some(timeout);
+    for e in entries.iter_mut() {
+        let decide = e.command.split_whitespace().any(|arg| arg == "--decide");
+        e.timeout = match timeout {
+            Some(t) if decide => Some(waiting_timeout(crate::cli::DECIDE_TIMEOUT_SECS, t)),
+            t => t,
+        };
+        e.run_async = run_async && !decide;
+    }
+}
+
 /// One-shot migration for hooks registered before `dynamic_message_enabled`
 /// defaulted to true: surgically append ` --dynamic` to existing agent-toast
 /// notification commands (no regeneration — hand-tuned messages and exe paths
@@ -918,7 +1158,7 @@ fn migrate_dynamic_message_default(content: &str) -> Option<String> {
 
     // Record the resolved choice — this is what makes the migration idempotent.
     root["agent_toast"]["dynamic_message_enabled"] = Value::Bool(true);
-    serde_json::to_string_pretty(&root).ok()
+    Some(rewrite(content, &root))
 }
 
 /// Run [`migrate_dynamic_message_default`] against `~/.claude/settings.json`.
@@ -930,29 +1170,108 @@ pub fn run_dynamic_message_migration() {
         return;
     };
     if let Some(updated) = migrate_dynamic_message_default(&content) {
-        match std::fs::write(&path, updated) {
+        match write_with_backup(&path, updated) {
             Ok(()) => log::info!("[migrate] appended --dynamic to existing agent-toast hooks"),
             Err(e) => log::warn!("[migrate] failed to write settings.json: {e}"),
         }
     }
 }
 
-/// Save hook config to ~/.claude/settings.json, preserving other fields
-#[tauri::command]
-pub fn save_hook_config(
-    app: tauri::AppHandle,
-    state: tauri::State<'_, crate::notification::NotificationManagerState>,
-    config: HookConfig,
-) -> Result<String, String> {
-    let path = settings_path();
+/// Parsed text of the settings file at `path`, or an empty object when the
+/// file is missing or empty. Invalid JSON is an error: saving over it would
+/// drop every hook and setting the file held.
+fn parse_settings_root(path: &Path, content: &str) -> Result<Value, String> {
+    if content.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    serde_json::from_str(content).map_err(|e| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        format!("{name} is not valid JSON: {e}")
+    })
+}
+
+/// The scope `config` registers hooks in, and that scope's settings file.
+fn hook_target(config: &HookConfig) -> Result<(Scope, PathBuf), String> {
+    let scope: Scope = config.hook_scope.parse()?;
+    if scope == Scope::User {
+        return Ok((scope, settings_path()));
+    }
+    if config.project_dir.trim().is_empty() {
+        return Err(format!("{scope} scope needs a project folder"));
+    }
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    Ok((
+        scope,
+        scope.settings_path(&home, Path::new(&config.project_dir)),
+    ))
+}
+
+/// [`hook_target`]'s file, with `user_file` standing in for the user scope's.
+fn scope_file(config: &HookConfig, user_file: &Path) -> Result<PathBuf, String> {
+    match hook_target(config)? {
+        (Scope::User, _) => Ok(user_file.to_path_buf()),
+        (_, path) => Ok(path),
+    }
+}
 
-    // Read existing settings or create new object
-    let mut root: Value = if let Ok(content) = std::fs::read_to_string(&path) {
-        serde_json::from_str(&content).unwrap_or_else(|_| Value::Object(Default::default()))
+/// The files saving `config` rewrites, with their new contents: the scope's
+/// settings file gets the hooks, `user_file` (~/.claude/settings.json) the
+/// `agent_toast` block. Only the agent-toast parts change; the rest of each
+/// file keeps its key order and formatting. Writes nothing.
+///
+/// When the scope changed since the last save, the agent-toast hooks are
+/// also removed from the previous scope's file, or every event would toast
+/// once per scope.
+fn hook_config_writes(
+    user_file: &Path,
+    config: &HookConfig,
+) -> Result<Vec<(PathBuf, String)>, String> {
+    let content = std::fs::read_to_string(user_file).unwrap_or_default();
+    let mut root = parse_settings_root(user_file, &content)?;
+    let target = scope_file(config, user_file)?;
+    // The last save's scope, from the block this one replaces.
+    let previous = scope_file(&parse_hook_config_from_root(&root), user_file)
+        .unwrap_or_else(|_| user_file.to_path_buf());
+
+    let mut writes = Vec::new();
+    if target == user_file {
+        root = apply_hook_config(root, config);
     } else {
-        Value::Object(Default::default())
-    };
+        let scoped = std::fs::read_to_string(&target).unwrap_or_default();
+        let merged = merge_agent_toast_hooks(
+            parse_settings_root(&target, &scoped)?,
+            &hook_entries(config),
+        );
+        writes.push((target.clone(), rewrite(&scoped, &merged)));
+        if previous == user_file {
+            root = remove_agent_toast_hooks(root);
+        }
+        write_agent_toast_settings(&mut root, config);
+    }
+    if previous != target && previous != user_file {
+        if let Ok(old) = std::fs::read_to_string(&previous) {
+            let before = parse_settings_root(&previous, &old)?;
+            let cleaned = remove_agent_toast_hooks(before.clone());
+            if cleaned != before {
+                writes.push((previous, rewrite(&old, &cleaned)));
+            }
+        }
+    }
+    writes.push((user_file.to_path_buf(), rewrite(&content, &root)));
+    Ok(writes)
+}
+
+/// `root` with `config` applied: agent-toast hook entries replaced (other
+/// hooks preserved) and the `agent_toast` block written. Writes nothing; the
+/// caller decides whether to save it.
+fn apply_hook_config(root: Value, config: &HookConfig) -> Value {
+    let mut root = merge_agent_toast_hooks(root, &hook_entries(config));
+    write_agent_toast_settings(&mut root, config);
+    root
+}
 
+/// The agent-toast hook entries `config` enables.
+fn hook_entries(config: &HookConfig) -> Vec<HookEntry> {
     let exe = exe_path_for_shell();
 
     // Build agent-toast hook entries.
@@ -964,194 +1283,185 @@ pub fn save_hook_config(
             event_key: "SessionStart",
             matcher: None,
             command: format!("{} --daemon", exe),
+            ..HookEntry::default()
         });
     }
     // SessionStart: add notification entry if enabled
     if config.session_start_enabled {
-        entries.push(HookEntry {
-            event_key: "SessionStart",
-            matcher: None,
-            command: format!(
-                "{} --event session_start --message \"{}\"",
-                exe, config.session_start_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "SessionStart",
+            None,
+            &config.session_start_message,
+            "",
+        ));
     }
 
     // CLI reads CLAUDE_PROJECT_DIR env var directly as title hint fallback,
     // so no --title arg needed in the hook command.
 
     if config.stop_enabled {
-        entries.push(HookEntry {
-            event_key: "Stop",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.stop_message
-            ),
-        });
+        entries.push(notify_entry(&exe, "Stop", None, &config.stop_message, ""));
     }
 
     if config.notification_permission_enabled {
-        entries.push(HookEntry {
-            event_key: "Notification",
-            matcher: Some("permission_prompt"),
-            command: format!(
-                "{} --event user_input_required --message \"{}\"",
-                exe, config.notification_permission_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "Notification",
+            Some("permission_prompt"),
+            &config.notification_permission_message,
+            "",
+        ));
     }
 
     if config.notification_elicitation_enabled {
-        entries.push(HookEntry {
-            event_key: "Notification",
-            matcher: Some("elicitation_dialog"),
-            command: format!(
-                "{} --event user_input_required --message \"{}\"",
-                exe, config.notification_elicitation_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "Notification",
+            Some("elicitation_dialog"),
+            &config.notification_elicitation_message,
+            "",
+        ));
     }
 
     if config.notification_idle_enabled {
-        entries.push(HookEntry {
-            event_key: "Notification",
-            matcher: Some("idle_prompt"),
-            command: format!(
-                "{} --event user_input_required --message \"{}\"",
-                exe, config.notification_idle_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "Notification",
+            Some("idle_prompt"),
+            &config.notification_idle_message,
+            "",
+        ));
     }
 
     if config.session_end_enabled {
-        entries.push(HookEntry {
-            event_key: "SessionEnd",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.session_end_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "SessionEnd",
+            None,
+            &config.session_end_message,
+            "",
+        ));
     }
 
     if config.subagent_stop_enabled {
-        entries.push(HookEntry {
-            event_key: "SubagentStop",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.subagent_stop_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "SubagentStop",
+            None,
+            &config.subagent_stop_message,
+            " --priority low",
+        ));
     }
 
     if config.pre_compact_enabled {
-        entries.push(HookEntry {
-            event_key: "PreCompact",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.pre_compact_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "PreCompact",
+            None,
+            &config.pre_compact_message,
+            "",
+        ));
     }
 
     if config.setup_enabled {
-        entries.push(HookEntry {
-            event_key: "Setup",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.setup_message
-            ),
-        });
+        entries.push(notify_entry(&exe, "Setup", None, &config.setup_message, ""));
     }
 
     if config.user_prompt_submit_enabled {
-        entries.push(HookEntry {
-            event_key: "UserPromptSubmit",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.user_prompt_submit_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "UserPromptSubmit",
+            None,
+            &config.user_prompt_submit_message,
+            "",
+        ));
     }
 
     if config.pre_tool_use_enabled {
-        entries.push(HookEntry {
-            event_key: "PreToolUse",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.pre_tool_use_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "PreToolUse",
+            None,
+            &config.pre_tool_use_message,
+            "",
+        ));
     }
 
     if config.post_tool_use_enabled {
-        entries.push(HookEntry {
-            event_key: "PostToolUse",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.post_tool_use_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "PostToolUse",
+            None,
+            &config.post_tool_use_message,
+            "",
+        ));
     }
 
     if config.post_tool_use_failure_enabled {
-        entries.push(HookEntry {
-            event_key: "PostToolUseFailure",
-            matcher: None,
-            command: format!(
-                "{} --event error --message \"{}\"",
-                exe, config.post_tool_use_failure_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "PostToolUseFailure",
+            None,
+            &config.post_tool_use_failure_message,
+            " --priority high",
+        ));
     }
 
     if config.permission_request_enabled {
-        entries.push(HookEntry {
-            event_key: "PermissionRequest",
-            matcher: None,
-            command: format!(
-                "{} --event user_input_required --message \"{}\"",
-                exe, config.permission_request_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "PermissionRequest",
+            None,
+            &config.permission_request_message,
+            "",
+        ));
     }
 
     if config.subagent_start_enabled {
-        entries.push(HookEntry {
-            event_key: "SubagentStart",
-            matcher: None,
-            command: format!(
-                "{} --event task_complete --message \"{}\"",
-                exe, config.subagent_start_message
-            ),
-        });
+        entries.push(notify_entry(
+            &exe,
+            "SubagentStart",
+            None,
+            &config.subagent_start_message,
+            " --priority low",
+        ));
     }
 
     // When dynamic mode is on, every notification command derives its body from
     // the hook's stdin JSON (the infrastructure `--daemon` entry stays untouched).
+    apply_template_flags(&mut entries, &config.templates);
     apply_dynamic_flag(&mut entries, config.dynamic_message_enabled);
+    apply_decide_flag(&mut entries, config.permission_decide_enabled);
+    apply_hook_options(&mut entries, config.hook_timeout_seconds, config.hook_async);
+    entries
+}
 
-    // Merge entries into root, preserving non-agent-toast hooks.
-    root = merge_agent_toast_hooks(root, &entries);
-
-    write_agent_toast_settings(&mut root, &config);
+/// Dry run of [`save_hook_config`]: the hook entries saving `config` would
+/// add, remove or change in its scope's settings file. Writes nothing.
+#[tauri::command]
+pub fn preview_hook_config(config: HookConfig) -> Result<HookDiff, String> {
+    let (_, target) = hook_target(&config)?;
+    let content = std::fs::read_to_string(&target).unwrap_or_default();
+    let before = parse_settings_root(&target, &content)?;
+    let after = merge_agent_toast_hooks(before.clone(), &hook_entries(&config));
+    Ok(diff_hooks(&before, &after))
+}
 
-    // Ensure .claude directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+/// Save hook config, preserving other fields: the hooks go to the scope's
+/// settings file, the `agent_toast` block always to ~/.claude/settings.json.
+#[tauri::command]
+pub fn save_hook_config(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, crate::notification::NotificationManagerState>,
+    config: HookConfig,
+) -> Result<String, String> {
+    let (_, target) = hook_target(&config)?;
+    // The previous version of each file goes to a backup.
+    for (path, contents) in hook_config_writes(&settings_path(), &config)? {
+        write_with_backup(&path, contents).map_err(|e| e.to_string())?;
     }
 
-    let json = serde_json::to_string_pretty(&root).map_err(|e| e.to_string())?;
-    std::fs::write(&path, &json).map_err(|e| e.to_string())?;
-
     // 사운드 복사본 정리: 설정에 참조된 파일만 남긴다 (기본값 복원 후 저장 시 전체 삭제)
     prune_sounds_dir(
         &sounds_dir(),
@@ -1170,7 +1480,7 @@ pub fn save_hook_config(
     // 트레이 메뉴 텍스트를 현재 locale에 맞게 갱신
     crate::update_tray_locale(&app);
 
-    let mut result = path.to_string_lossy().to_string();
+    let mut result = target.to_string_lossy().to_string();
     if config.codex_enabled {
         result.push('\n');
         result.push_str(&codex_config_path().to_string_lossy());
@@ -1243,6 +1553,11 @@ fn read_hook_config() -> HookConfig {
     parse_hook_config_from_json(&content)
 }
 
+/// 설정 파일의 `agent_toast.redact_patterns`를 포함한 비밀값 마스킹기
+pub fn load_redactor() -> agent_toast_core::dynamic::Redactor {
+    agent_toast_core::dynamic::Redactor::from_settings_file(settings_path())
+}
+
 /// 설정 파일에서 auto_close_on_focus 값만 빠르게 읽기
 pub fn load_auto_close_on_focus() -> bool {
     let path = settings_path();
@@ -1353,7 +1668,7 @@ fn set_theme_in_json(content: &str, theme: &str) -> String {
     at.as_object_mut()
         .unwrap()
         .insert("theme".into(), Value::String(theme.to_string()));
-    serde_json::to_string_pretty(&root).unwrap_or_else(|_| content.to_string())
+    rewrite(content, &root)
 }
 
 /// settings.json 에서 theme 읽기 — 실패 시 "system".
@@ -1401,7 +1716,7 @@ fn set_global_stats_in_json(content: &str, enabled: bool) -> String {
     at.as_object_mut()
         .unwrap()
         .insert("global_stats_enabled".into(), Value::Bool(enabled));
-    serde_json::to_string_pretty(&root).unwrap_or_else(|_| content.to_string())
+    rewrite(content, &root)
 }
 
 /// settings.json에서 global_stats_enabled 읽기 — 파일 없음/실패 시 true.
@@ -1469,42 +1784,133 @@ fn codex_config_path() -> PathBuf {
         .join("config.toml")
 }
 
+/// Point Codex's `notify` at agent-toast (chaining any program already
+/// there), or take it back out. Comments and the rest of config.toml are
+/// kept; nothing is written when nothing changes.
 fn save_codex_config(enabled: bool) -> Result<(), String> {
     let path = codex_config_path();
-
-    // Read existing config or start fresh
-    let mut doc: toml::Table = if let Ok(content) = std::fs::read_to_string(&path) {
-        content
-            .parse::<toml::Table>()
-            .unwrap_or_else(|_| toml::Table::new())
-    } else {
-        toml::Table::new()
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {e}", path.display())),
     };
 
-    if enabled {
-        let exe = exe_path_unquoted();
-        let notify_value = toml::Value::Array(vec![
-            toml::Value::String(exe),
-            toml::Value::String("--codex".into()),
-        ]);
-        doc.insert("notify".into(), notify_value);
+    let updated = if enabled {
+        let program = [exe_path_unquoted(), codex_config::CODEX_FLAG.into()];
+        Some(codex_config::install(&content, &program)?)
     } else {
-        doc.remove("notify");
+        // With Codex off, a config.toml we can't parse isn't ours to fix.
+        codex_config::uninstall(&content).unwrap_or_else(|e| {
+            log::warn!("[codex] left config.toml untouched: {e}");
+            None
+        })
+    };
+    match updated {
+        Some(new) if new != content => write_with_backup(&path, new).map_err(|e| e.to_string()),
+        _ => Ok(()),
     }
+}
 
-    // Ensure .codex directory exists
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+/// A backup of one of the files agent-toast writes, as listed in the setup
+/// GUI.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SettingsBackup {
+    /// `"claude"` for ~/.claude/settings.json, `"codex"` for
+    /// ~/.codex/config.toml, `"project"` / `"local"` for the hook scope's
+    /// file.
+    pub file: String,
+    pub path: String,
+    pub created: String,
+}
+
+/// Files whose writes are backed up, keyed as in [`SettingsBackup::file`].
+fn backed_up_files() -> Vec<(&'static str, PathBuf)> {
+    let mut files = vec![("claude", settings_path()), ("codex", codex_config_path())];
+    if let Ok((scope @ (Scope::Project | Scope::Local), path)) = hook_target(&read_hook_config()) {
+        if path != settings_path() {
+            files.push((scope.as_str(), path));
+        }
     }
+    files
+}
+
+/// Backups of settings.json and the Codex config.toml, newest first.
+#[tauri::command]
+pub fn list_settings_backups() -> Vec<SettingsBackup> {
+    let mut all: Vec<SettingsBackup> = backed_up_files()
+        .into_iter()
+        .flat_map(|(file, path)| {
+            backup::list(&home_dir(), &path)
+                .into_iter()
+                .map(move |b| SettingsBackup {
+                    file: file.into(),
+                    path: b.path.to_string_lossy().into_owned(),
+                    created: b.created,
+                })
+        })
+        .collect();
+    all.sort_by(|a, b| b.created.cmp(&a.created));
+    all
+}
+
+/// agent-toast hooks registered in more than one scope (user, and the
+/// project / local files of `config.project_dir`).
+#[tauri::command]
+pub fn find_duplicate_hooks(config: HookConfig) -> Vec<DuplicateHook> {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    let project = PathBuf::from(&config.project_dir);
+    let scopes: &[Scope] = if config.project_dir.trim().is_empty() {
+        &[Scope::User]
+    } else {
+        &Scope::ALL
+    };
+    let mut paths = Vec::new();
+    let roots: Vec<(Scope, Value)> = scopes
+        .iter()
+        .filter_map(|&scope| {
+            // The project folder may be the home folder: one file, read once.
+            let path = scope.settings_path(&home, &project);
+            if paths.contains(&path) {
+                return None;
+            }
+            let content = std::fs::read_to_string(&path).ok();
+            paths.push(path);
+            Some((scope, serde_json::from_str(&content?).ok()?))
+        })
+        .collect();
+    let roots: Vec<(Scope, &Value)> = roots.iter().map(|(s, r)| (*s, r)).collect();
+    duplicate_hooks(&roots)
+}
 
-    let content = toml::to_string_pretty(&doc).map_err(|e| e.to_string())?;
-    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+/// Roll the file a backup belongs to back to it (the current version is
+/// backed up first), then apply the restored settings like a save would.
+#[tauri::command]
+pub fn restore_settings_backup(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, crate::notification::NotificationManagerState>,
+    path: String,
+) -> Result<(), String> {
+    let backup_path = PathBuf::from(path);
+    let (_, target) = backed_up_files()
+        .into_iter()
+        .find(|(_, file)| {
+            backup::list(&home_dir(), file)
+                .iter()
+                .any(|b| b.path == backup_path)
+        })
+        .ok_or_else(|| format!("unknown backup: {}", backup_path.display()))?;
+    backup::restore(&home_dir(), &target, &backup_path).map_err(|e| e.to_string())?;
+
+    crate::sync_http_server(&app).map_err(|e| format!("HTTP 서버 시작 실패: {e}"))?;
+    crate::notification::reposition_all(&app, &state);
+    crate::update_tray_locale(&app);
     Ok(())
 }
 
 /// Extract the command string from a hook entry if it belongs to agent-toast.
-/// Delegates detection to `is_agent_toast_cmd` (core) so hyphen/underscore/space
-/// variants and "Agent Toast" productName are all matched consistently.
+/// Delegates detection to `is_agent_toast_cmd` (core): commands carrying the
+/// ownership marker, plus unmarked ones from older versions whose program is
+/// the agent-toast binary ("Agent Toast" productName included).
 fn extract_agent_toast_cmd(entry: &Value) -> Option<&str> {
     let hooks_arr = entry.get("hooks")?.as_array()?;
     for hook in hooks_arr {
@@ -1541,27 +1947,51 @@ fn build_hook_entry(matcher: Option<&str>, command: &str, timeout: Option<u32>)
 /// Extract --message value from a command string.
 /// Supports: --message="...", --message "...", --message=value
 fn extract_message(cmd: &str) -> Option<String> {
-    // Match --message="..." or --message "..."
-    let patterns = ["--message=\"", "--message \""];
-    for pat in patterns {
-        if let Some(start) = cmd.find(pat) {
-            let msg_start = start + pat.len();
-            let rest = &cmd[msg_start..];
-            if let Some(end) = rest.find('"') {
-                return Some(rest[..end].to_string());
+    extract_flag_value(cmd, "--message")
+}
+
+/// Value of `flag` in a hook command: `flag="..."`, `flag "..."` or `flag=value`.
+fn extract_flag_value(cmd: &str, flag: &str) -> Option<String> {
+    // Match --flag="..." or --flag "..."
+    for pat in [format!("{flag}=\""), format!("{flag} \"")] {
+        if let Some(start) = cmd.find(&pat) {
+            if let Some(value) = unquote(&cmd[start + pat.len()..]) {
+                return Some(value);
             }
         }
     }
-    // Match --message=value (no quotes)
-    if let Some(start) = cmd.find("--message=") {
-        let msg_start = start + "--message=".len();
-        let rest = &cmd[msg_start..];
+    // Match --flag=value (no quotes)
+    let pat = format!("{flag}=");
+    if let Some(start) = cmd.find(&pat) {
+        let value_start = start + pat.len();
+        let rest = &cmd[value_start..];
         let end = rest.find(' ').unwrap_or(rest.len());
         return Some(rest[..end].to_string());
     }
     None
 }
 
+/// The double-quoted value `rest` starts with (opening quote already
+/// consumed), up to the closing quote, with [`shell_quote`]'s escapes
+/// undone. `None` if the quote is never closed.
+fn unquote(rest: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => match chars.next() {
+                Some(e @ ('\\' | '"' | '$' | '`')) => value.push(e),
+                // bash keeps any other backslash, e.g. in `C:\Users`.
+                Some(other) => value.extend(['\\', other]),
+                None => value.push('\\'),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1611,6 +2041,18 @@ mod tests {
         assert!(root["agent_toast"]["notification_sound_file"].is_null());
     }
 
+    #[test]
+    fn written_settings_keys_are_in_core_schema() {
+        // 새 설정 키를 추가하면 agent_toast_core::schema 에도 설명을 추가해야 한다
+        let mut root = serde_json::json!({});
+        write_agent_toast_settings(&mut root, &HookConfig::default());
+        let schema = agent_toast_core::schema::settings_block();
+        let described = schema["properties"].as_object().unwrap();
+        for key in root["agent_toast"].as_object().unwrap().keys() {
+            assert!(described.contains_key(key), "`{key}` missing from schema");
+        }
+    }
+
     #[test]
     fn copy_and_prune_sounds_dir() {
         let base =
@@ -2750,6 +3192,20 @@ mod tests {
         assert_eq!(config.auto_dismiss_seconds, 0);
     }
 
+    #[test]
+    fn parse_dedupe_window_seconds() {
+        let json = r#"{"agent_toast": {"dedupe_window_seconds": 5}}"#;
+        assert_eq!(parse_hook_config_from_json(json).dedupe_window_seconds, 5);
+        let json = r#"{"agent_toast": {"dedupe_window_seconds": 0}}"#;
+        assert_eq!(parse_hook_config_from_json(json).dedupe_window_seconds, 0);
+    }
+
+    #[test]
+    fn parse_dedupe_window_seconds_missing_uses_default() {
+        let config = parse_hook_config_from_json(r#"{"agent_toast": {}}"#);
+        assert_eq!(config.dedupe_window_seconds, 30);
+    }
+
     #[test]
     fn parse_auto_dismiss_seconds_missing_defaults_to_zero() {
         let json = r#"{"agent_toast": {}}"#;
@@ -3101,6 +3557,16 @@ mod theme_tests {
         assert_eq!(v["otherTool"]["x"], 1);
     }
 
+    #[test]
+    fn set_theme_in_json_keeps_key_order_and_formatting() {
+        let json = "{\n    \"zeta\": 1,\n    \"agent_toast\": {\n        \"locale\": \"ko\"\n    },\n    \"alpha\": [1, 2]\n}\n";
+        let out = set_theme_in_json(json, "dark");
+        assert_eq!(
+            out,
+            "{\n    \"zeta\": 1,\n    \"agent_toast\": {\n        \"locale\": \"ko\",\n        \"theme\": \"dark\"\n    },\n    \"alpha\": [1, 2]\n}\n"
+        );
+    }
+
     #[test]
     fn set_theme_in_json_creates_structure_when_empty() {
         let out = set_theme_in_json("", "light");
@@ -3128,11 +3594,13 @@ mod theme_tests {
                 event_key: "SessionStart",
                 matcher: None,
                 command: "\"at.exe\" --daemon".into(),
+                ..HookEntry::default()
             },
             HookEntry {
                 event_key: "Stop",
                 matcher: None,
                 command: "\"at.exe\" --event task_complete --message \"x\"".into(),
+                ..HookEntry::default()
             },
         ];
         apply_dynamic_flag(&mut entries, true);
@@ -3152,11 +3620,373 @@ mod theme_tests {
             event_key: "Stop",
             matcher: None,
             command: "\"at.exe\" --event task_complete".into(),
+            ..HookEntry::default()
         }];
         apply_dynamic_flag(&mut entries, false);
         assert!(!entries[0].command.contains("--dynamic"));
     }
 
+    #[test]
+    fn preview_of_already_saved_config_is_empty() {
+        let config = HookConfig::default();
+        let empty = Value::Object(Default::default());
+        let saved = apply_hook_config(empty.clone(), &config);
+        let first = diff_hooks(&empty, &saved);
+        assert!(first.entries.iter().any(|e| e.event_key == "Stop"
+            && matches!(
+                e.change,
+                agent_toast_core::hook_config::HookChange::Added { .. }
+            )));
+        assert!(diff_hooks(&saved, &apply_hook_config(saved.clone(), &config)).is_empty());
+    }
+
+    #[test]
+    fn parse_settings_root_rejects_invalid_json() {
+        let path = Path::new("/p/.claude/settings.local.json");
+        assert_eq!(
+            parse_settings_root(path, "").unwrap(),
+            serde_json::json!({})
+        );
+        assert_eq!(
+            parse_settings_root(path, "{\"model\": \"opus\"}").unwrap(),
+            serde_json::json!({"model": "opus"})
+        );
+        // A half-edited file must not be saved over as `{}`.
+        let err = parse_settings_root(path, "{\"hooks\": {").unwrap_err();
+        assert!(
+            err.starts_with("settings.local.json is not valid JSON"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn changing_scope_removes_hooks_from_previous_file() {
+        let base =
+            std::env::temp_dir().join(format!("agent-toast-test-scope-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let user_file = base.join("home").join(".claude").join("settings.json");
+        let project = base.join("app");
+        let project_file = project.join(".claude").join("settings.json");
+        let local_file = project.join(".claude").join("settings.local.json");
+        std::fs::create_dir_all(user_file.parent().unwrap()).unwrap();
+        std::fs::write(
+            &user_file,
+            r#"{"hooks": {"Stop": [{"hooks": [{"type": "command", "command": "afplay done.aiff"}]}]}}"#,
+        )
+        .unwrap();
+
+        let save = |scope: &str| {
+            let config = HookConfig {
+                hook_scope: scope.into(),
+                project_dir: project.to_string_lossy().into_owned(),
+                ..HookConfig::default()
+            };
+            for (path, contents) in hook_config_writes(&user_file, &config).unwrap() {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, contents).unwrap();
+            }
+        };
+        // (agent-toast 훅 수, 다른 훅 수)
+        let hooks = |path: &Path| -> (usize, usize) {
+            let root: Value =
+                serde_json::from_str(&std::fs::read_to_string(path).unwrap_or_default())
+                    .unwrap_or_default();
+            let commands: Vec<String> = root["hooks"]
+                .as_object()
+                .into_iter()
+                .flat_map(|events| events.values())
+                .filter_map(Value::as_array)
+                .flatten()
+                .filter_map(|group| group["hooks"].as_array())
+                .flatten()
+                .filter_map(|hook| hook["command"].as_str().map(str::to_string))
+                .collect();
+            let ours = commands.iter().filter(|c| is_agent_toast_cmd(c)).count();
+            (ours, commands.len() - ours)
+        };
+
+        save("user");
+        let (ours, _) = hooks(&user_file);
+        assert!(ours > 0);
+
+        // 사용자 → 프로젝트: 사용자 파일의 훅은 지우고 다른 훅은 남긴다
+        save("project");
+        assert_eq!(hooks(&project_file), (ours, 0));
+        assert_eq!(hooks(&user_file), (0, 1));
+
+        // 프로젝트 → 로컬
+        save("local");
+        assert_eq!(hooks(&local_file), (ours, 0));
+        assert_eq!(hooks(&project_file), (0, 0));
+        assert_eq!(hooks(&user_file), (0, 1));
+
+        // 로컬 → 사용자
+        save("user");
+        assert_eq!(hooks(&user_file), (ours, 1));
+        assert_eq!(hooks(&local_file), (0, 0));
+
+        // 같은 범위로 다시 저장하면 다른 파일은 건드리지 않는다
+        let writes = hook_config_writes(
+            &user_file,
+            &HookConfig {
+                project_dir: project.to_string_lossy().into_owned(),
+                ..HookConfig::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].0, user_file);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn hook_target_resolves_scope_files() {
+        let config = HookConfig::default();
+        assert_eq!(
+            hook_target(&config).unwrap(),
+            (Scope::User, settings_path())
+        );
+
+        let config = HookConfig {
+            hook_scope: "local".into(),
+            project_dir: "/src/app".into(),
+            ..HookConfig::default()
+        };
+        assert_eq!(
+            hook_target(&config).unwrap(),
+            (
+                Scope::Local,
+                Path::new("/src/app")
+                    .join(".claude")
+                    .join("settings.local.json")
+            )
+        );
+
+        let no_dir = HookConfig {
+            hook_scope: "project".into(),
+            ..HookConfig::default()
+        };
+        assert!(hook_target(&no_dir).is_err());
+        let unknown = HookConfig {
+            hook_scope: "global".into(),
+            ..HookConfig::default()
+        };
+        assert!(hook_target(&unknown).is_err());
+    }
+
+    #[test]
+    fn hook_scope_roundtrips_through_agent_toast_block() {
+        let cfg = HookConfig {
+            hook_scope: "project".into(),
+            project_dir: "/src/app".into(),
+            ..HookConfig::default()
+        };
+        let mut root = Value::Object(Default::default());
+        write_agent_toast_settings(&mut root, &cfg);
+        let parsed = parse_hook_config_from_root(&root);
+        assert_eq!(parsed.hook_scope, "project");
+        assert_eq!(parsed.project_dir, "/src/app");
+        assert_eq!(parse_hook_config_from_json("{}").hook_scope, "user");
+    }
+
+    #[test]
+    fn apply_decide_flag_targets_permission_request_only() {
+        let mut entries = vec![
+            HookEntry {
+                event_key: "Stop",
+                matcher: None,
+                command: "\"at.exe\" --event task_complete".into(),
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "PermissionRequest",
+                matcher: None,
+                command: "\"at.exe\" --event user_input_required".into(),
+                ..HookEntry::default()
+            },
+        ];
+        apply_decide_flag(&mut entries, true);
+        assert!(!entries[0].command.contains("--decide"));
+        assert!(entries[1].command.ends_with(" --decide"));
+
+        let mut entries = vec![HookEntry {
+            event_key: "PermissionRequest",
+            matcher: None,
+            command: "\"at.exe\" --event user_input_required".into(),
+            ..HookEntry::default()
+        }];
+        apply_decide_flag(&mut entries, false);
+        assert!(!entries[0].command.contains("--decide"));
+    }
+
+    #[test]
+    fn apply_hook_options_spares_decide_from_async() {
+        let mut entries = vec![
+            HookEntry {
+                event_key: "Stop",
+                command: "\"at.exe\" --event task_complete".into(),
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "PermissionRequest",
+                command: "\"at.exe\" --event user_input_required --decide".into(),
+                ..HookEntry::default()
+            },
+        ];
+        apply_hook_options(&mut entries, 10, true);
+        assert_eq!(entries[0].timeout, Some(10));
+        assert!(entries[0].run_async);
+        assert_eq!(entries[1].timeout, Some(60), "대기 50초 + 10초");
+        assert!(!entries[1].run_async, "decide 응답은 Claude가 읽어야 함");
+
+        apply_hook_options(&mut entries, 0, false);
+        assert!(entries.iter().all(|e| e.timeout.is_none() && !e.run_async));
+    }
+
+    #[test]
+    fn apply_template_flags_by_hook_item() {
+        let mut entries = vec![
+            HookEntry {
+                event_key: "SessionStart",
+                matcher: None,
+                command: "\"at.exe\" --daemon".into(),
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "Notification",
+                matcher: Some("permission_prompt"),
+                command: "\"at.exe\" --event user_input_required --message \"x\"".into(),
+                ..HookEntry::default()
+            },
+            HookEntry {
+                event_key: "Stop",
+                matcher: None,
+                command: "\"at.exe\" --event task_complete --message \"y\"".into(),
+                ..HookEntry::default()
+            },
+        ];
+        let templates = BTreeMap::from([
+            ("session_start".to_string(), "{cwd}".to_string()),
+            (
+                "notification_permission".to_string(),
+                "{tool_name}: {tool_input.command|80}".to_string(),
+            ),
+            ("stop".to_string(), "  ".to_string()),
+        ]);
+        apply_template_flags(&mut entries, &templates);
+        assert_eq!(entries[0].command, "\"at.exe\" --daemon");
+        assert!(entries[1]
+            .command
+            .ends_with(r#" --template "{tool_name}: {tool_input.command|80}""#));
+        assert!(
+            !entries[2].command.contains("--template"),
+            "빈 템플릿은 무시"
+        );
+    }
+
+    #[test]
+    fn template_with_quotes_round_trips() {
+        let tpl = r#"{tool_name} "{tool_input.command|40}" costs $5 in `sh` \ C:\tmp"#;
+        let mut entries = vec![HookEntry {
+            event_key: "PermissionRequest",
+            matcher: None,
+            command: "\"agent-toast.exe\" --event user_input_required --message \"x\"".into(),
+            ..HookEntry::default()
+        }];
+        let templates = BTreeMap::from([("permission_request".to_string(), tpl.to_string())]);
+        apply_template_flags(&mut entries, &templates);
+        apply_dynamic_flag(&mut entries, true);
+        assert!(entries[0].command.ends_with(" --dynamic"));
+        assert_eq!(
+            extract_flag_value(&entries[0].command, "--template").as_deref(),
+            Some(tpl)
+        );
+        assert_eq!(
+            extract_flag_value(&entries[0].command, "--message").as_deref(),
+            Some("x")
+        );
+
+        let json = serde_json::json!({
+            "hooks": {"PermissionRequest": [{"hooks": [{"type": "command", "command": entries[0].command}]}]}
+        });
+        let config = parse_hook_config_from_json(&json.to_string());
+        assert_eq!(
+            config
+                .templates
+                .get("permission_request")
+                .map(String::as_str),
+            Some(tpl)
+        );
+
+        // bash가 실제로 같은 문자열을 넘기는지 확인
+        #[cfg(unix)]
+        {
+            let out = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(format!("printf %s {}", shell_quote(tpl)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&out.stdout), tpl);
+        }
+    }
+
+    #[test]
+    fn parse_templates_from_hook_commands() {
+        let json = serde_json::json!({
+            "hooks": {
+                "PermissionRequest": [{"hooks": [{"type": "command", "command":
+                    "agent-toast.exe --event user_input_required --message \"권한\" --template \"{tool_name}: {tool_input.command}\" --dynamic"}]}],
+                "Notification": [{"matcher": "idle_prompt", "hooks": [{"type": "command", "command":
+                    "agent-toast.exe --event user_input_required --message \"idle\" --template \"{last_assistant_message|80}\""}]}],
+                "Stop": [{"hooks": [{"type": "command", "command":
+                    "agent-toast.exe --event task_complete --message \"done\""}]}]
+            }
+        });
+        let config = parse_hook_config_from_json(&json.to_string());
+        assert_eq!(
+            config
+                .templates
+                .get("permission_request")
+                .map(String::as_str),
+            Some("{tool_name}: {tool_input.command}")
+        );
+        assert_eq!(
+            config
+                .templates
+                .get("notification_idle")
+                .map(String::as_str),
+            Some("{last_assistant_message|80}")
+        );
+        assert_eq!(config.permission_request_message, "권한");
+        assert!(!config.templates.contains_key("stop"));
+    }
+
+    #[test]
+    fn parse_permission_decide_enabled() {
+        let json = r#"{"agent_toast":{"permission_decide_enabled":true}}"#;
+        assert!(parse_hook_config_from_json(json).permission_decide_enabled);
+        assert!(!parse_hook_config_from_json("{}").permission_decide_enabled);
+    }
+
+    #[test]
+    fn hook_options_roundtrip_through_agent_toast_block() {
+        let config = HookConfig {
+            hook_timeout_seconds: 0,
+            hook_async: true,
+            ..HookConfig::default()
+        };
+        let mut root = serde_json::json!({});
+        write_agent_toast_settings(&mut root, &config);
+        let parsed = parse_hook_config_from_json(&root.to_string());
+        assert_eq!(parsed.hook_timeout_seconds, 0);
+        assert!(parsed.hook_async);
+
+        let defaults = parse_hook_config_from_json("{}");
+        assert_eq!(defaults.hook_timeout_seconds, DEFAULT_TIMEOUT_SECS);
+        assert!(!defaults.hook_async);
+    }
+
     /// 키 없음 + agent-toast 훅 존재 시 settings.json 의 전형적인 형태
     fn legacy_settings_json() -> String {
         serde_json::json!({
@@ -21,6 +21,9 @@ pub struct CounterSet {
     pub closed_focus: u64,
     pub skipped_focused: u64,
     pub skipped_ratelimit: u64,
+    /// 같은 dedupe_key가 중복 창 안에 다시 와서 버려진 횟수 (이전 stats.json 호환용 default)
+    #[serde(default)]
+    pub skipped_duplicate: u64,
 }
 
 /// 글로벌 통계 동기화 상태 — stats.json의 예약 필드 `synced`에 저장된다.
@@ -67,11 +70,13 @@ impl Stats {
     }
 
     /// Apply `f` to both the `(event, source)` cell and the local/remote origin
-    /// cell. No-op for updater (app-internal, not user behavior).
+    /// cell. No-op for updater (app-internal, not user behavior). Sources no
+    /// adapter knows share one `other` column.
     fn bump(&mut self, event: &str, source: &str, remote: bool, f: impl Fn(&mut CounterSet)) {
         if source == "updater" {
             return;
         }
+        let source = agent_toast_core::source::stats_key(source);
         let cell = self
             .counts
             .entry(event.to_string())
@@ -96,6 +101,10 @@ impl Stats {
         self.bump(event, source, remote, |c| c.skipped_ratelimit += 1);
     }
 
+    pub fn record_skipped_duplicate(&mut self, event: &str, source: &str, remote: bool) {
+        self.bump(event, source, remote, |c| c.skipped_duplicate += 1);
+    }
+
     pub fn record_terminal(
         &mut self,
         reason: CloseReason,
@@ -211,6 +220,10 @@ pub fn record_skipped_ratelimit(state: &StatsState, event: &str, source: &str, r
     with_dirty(state, |s| s.record_skipped_ratelimit(event, source, remote));
 }
 
+pub fn record_skipped_duplicate(state: &StatsState, event: &str, source: &str, remote: bool) {
+    with_dirty(state, |s| s.record_skipped_duplicate(event, source, remote));
+}
+
 pub fn record_terminal(
     state: &StatsState,
     reason: CloseReason,
@@ -328,6 +341,33 @@ mod tests {
         assert_eq!(s.counts["brand_new_event"]["codex"].skipped_ratelimit, 1);
     }
 
+    #[test]
+    fn sources_bucket_by_adapter() {
+        let mut s = fresh();
+        s.record_shown("task_complete", "Gemini", false);
+        s.record_shown("task_complete", "my-ci-bot", false);
+        s.record_shown("task_complete", "another-script", false);
+        assert_eq!(s.counts["task_complete"]["gemini"].shown, 1);
+        assert_eq!(s.counts["task_complete"]["other"].shown, 2);
+    }
+
+    #[test]
+    fn skipped_duplicate_bumps_counts_and_origin() {
+        let mut s = fresh();
+        s.record_skipped_duplicate("task_complete", "claude", true);
+        assert_eq!(s.counts["task_complete"]["claude"].skipped_duplicate, 1);
+        assert_eq!(s.origin["remote"].skipped_duplicate, 1);
+    }
+
+    #[test]
+    fn counter_set_without_skipped_duplicate_still_loads() {
+        // skipped_duplicate 이전에 저장된 stats.json도 corrupt 처리 없이 읽혀야 한다.
+        let json = r#"{"version":1,"since":"x","counts":{"e":{"claude":{"shown":3,"activated":1,"closed_manual":0,"closed_timeout":0,"closed_focus":0,"skipped_focused":0,"skipped_ratelimit":0}}},"origin":{}}"#;
+        let s: Stats = serde_json::from_str(json).unwrap();
+        assert_eq!(s.counts["e"]["claude"].shown, 3);
+        assert_eq!(s.counts["e"]["claude"].skipped_duplicate, 0);
+    }
+
     #[test]
     fn origin_total_equals_counts_total() {
         let mut s = fresh();
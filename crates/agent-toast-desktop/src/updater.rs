@@ -204,6 +204,12 @@ fn show_update_notification(app: &AppHandle, state: &NotificationManagerState, v
         process_tree: Some(vec![]),
         source: "updater".into(),
         hostname: None,
+        schema_version: agent_toast_core::wire::SCHEMA_VERSION,
+        dedupe_key: None,
+        actions: vec![],
+        priority: agent_toast_core::wire::Priority::Normal,
+        context: agent_toast_core::wire::HookContext::default(),
+        extra: Default::default(),
     };
 
     show_notification(app, state, req);
@@ -265,6 +271,12 @@ fn show_update_completed_notification(
         process_tree: Some(vec![]),
         source: "updater".into(),
         hostname: None,
+        schema_version: agent_toast_core::wire::SCHEMA_VERSION,
+        dedupe_key: None,
+        actions: vec![],
+        priority: agent_toast_core::wire::Priority::Normal,
+        context: agent_toast_core::wire::HookContext::default(),
+        extra: Default::default(),
     };
 
     show_notification(app, state, req);
@@ -2,10 +2,15 @@
 //!
 //! Invoked from Claude Code hooks on Linux servers to POST notifications
 //! to the Windows desktop's HTTP receiver. Registers/removes its own hook
-//! entries via `init` and `uninstall`.
+//! entries via `init` and `uninstall`, and rolls settings.json back to a
+//! backup via `restore`.
 
+use agent_toast_core::hook_config::{Scope, DEFAULT_TIMEOUT_SECS};
 use clap::{Parser, Subcommand};
 
+/// Default `decide --timeout`: seconds to wait for Allow or Deny.
+const DECIDE_TIMEOUT_SECS: u64 = 50;
+
 #[derive(Parser, Debug)]
 #[command(
     name = "agent-toast-send",
@@ -24,16 +29,31 @@ struct Cli {
 enum Command {
     /// Send a single notification (default when no subcommand is given)
     Send(SendArgs),
-    /// Register default hooks in ~/.claude/settings.json
+    /// Register default hooks in ~/.claude/settings.json (or a project's,
+    /// with --scope)
     Init(InitArgs),
-    /// Remove agent-toast hooks from ~/.claude/settings.json
-    Uninstall,
+    /// Remove agent-toast hooks from ~/.claude/settings.json (or a
+    /// project's, with --scope)
+    Uninstall(UninstallArgs),
+    /// List settings.json backups, or restore one by its number
+    Restore(RestoreArgs),
+    /// Print the desktop receiver's supported schema version and features
+    Capabilities(CapabilitiesArgs),
+    /// Answer a PermissionRequest hook from the toast: wait for Allow/Deny
+    /// and print Claude Code's hook decision JSON
+    Decide(DecideArgs),
+    /// Send queued notifications (one request JSON per line) in one call
+    Batch(BatchArgs),
+    /// Print a JSON Schema for the notify request body or settings.json
+    Schema(SchemaArgs),
 }
 
 #[derive(clap::Args, Debug, Default, Clone)]
 struct SendArgs {
     #[arg(long)]
     url: Option<String>,
+    /// Toast event. Optional in a Claude Code hook: the stdin payload's
+    /// hook_event_name (and notification_type) picks it.
     #[arg(long)]
     event: Option<String>,
     #[arg(long)]
@@ -46,12 +66,54 @@ struct SendArgs {
     /// tool description), falling back to --message on failure.
     #[arg(long)]
     dynamic: bool,
+    /// Body from the hook's stdin JSON with placeholders, e.g.
+    /// "{tool_name}: {tool_input.command|80}". Takes precedence over
+    /// --dynamic; falls back to --message when it renders empty.
+    #[arg(long)]
+    template: Option<String>,
+    /// Idempotency key: the desktop shows only the first notification with a
+    /// given key within its dedupe window (hook retries, duplicate hooks).
+    #[arg(long)]
+    dedupe_key: Option<String>,
+    /// Action button as ID=LABEL (repeatable, at most 3). The command waits
+    /// for the toast to close and prints the id of the clicked action.
+    #[arg(long = "action", value_name = "ID=LABEL")]
+    actions: Vec<agent_toast_core::wire::ToastAction>,
+    /// Seconds to wait for an action click before giving up.
+    #[arg(long, default_value_t = 120)]
+    action_timeout: u64,
+    /// low | normal | high | critical. Higher priorities play sound, stay on
+    /// screen longer and are never dropped by the desktop's rate limiter.
+    #[arg(long, default_value = "normal")]
+    priority: agent_toast_core::wire::Priority,
+    /// Metadata row as KEY=VALUE (repeatable), shown under the message, e.g.
+    /// `--field branch=main --field job=1234`.
+    #[arg(long = "field", value_name = "KEY=VALUE", value_parser = agent_toast_core::wire::parse_extra_field)]
+    fields: Vec<(String, String)>,
+    /// Codex CLI notify payload. Codex appends it as the last argument, so
+    /// register `notify = ["agent-toast-send", "--url", "<url>", "--codex"]`.
+    /// Replaces --event/--message and the --dynamic body.
+    #[arg(long, value_name = "JSON")]
+    codex: Option<String>,
+    /// With --codex: one argument of another notify program to forward the
+    /// payload to (repeatable, before --codex), e.g. `--chain python3
+    /// --chain notify.py`.
+    #[arg(long = "chain", value_name = "ARG", allow_hyphen_values = true)]
+    chain: Vec<String>,
+    /// Agent that runs the command: claude | codex | gemini | cursor |
+    /// windsurf | aider. For gemini, cursor and windsurf the hook payload on
+    /// stdin supplies event, body, title and session (--event becomes
+    /// optional); aider's notification command has no payload.
     #[arg(long, default_value = "claude")]
     source: String,
     #[arg(long, default_value_t = 2000)]
     timeout_ms: u64,
     #[arg(long)]
     quiet: bool,
+    /// Marks a hook command as registered by agent-toast
+    /// (`hook_config::MARKER`); otherwise ignored.
+    #[arg(long = "agent-toast-managed", hide = true)]
+    _managed: bool,
 }
 
 #[derive(clap::Args, Debug)]
@@ -64,30 +126,181 @@ struct InitArgs {
     /// (or tool description) instead of the fixed text.
     #[arg(long)]
     dynamic: bool,
+    /// Register a PermissionRequest hook that answers from the toast
+    /// (`decide`) instead of the permission_prompt notification.
+    #[arg(long)]
+    decide: bool,
+    /// Seconds Claude Code lets each hook run before killing it, so an
+    /// unreachable desktop can't stall the agent (0: Claude's 60 s default).
+    /// The decide hook gets its wait on top.
+    #[arg(long, default_value_t = DEFAULT_TIMEOUT_SECS)]
+    hook_timeout: u32,
+    /// Run the notification hooks in the background so Claude Code never
+    /// waits for them (not the decide hook, whose answer Claude reads)
+    #[arg(long = "async")]
+    run_async: bool,
+    #[command(flatten)]
+    scope: ScopeArgs,
+    #[command(flatten)]
+    preview: PreviewArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct UninstallArgs {
+    #[command(flatten)]
+    scope: ScopeArgs,
+    #[command(flatten)]
+    preview: PreviewArgs,
+}
+
+#[derive(clap::Args, Debug)]
+struct RestoreArgs {
+    /// Backup to restore, as numbered by the listing (1 = newest). Lists
+    /// the backups when omitted.
+    index: Option<usize>,
+    #[command(flatten)]
+    scope: ScopeArgs,
+}
+
+/// Which settings.json `init` / `uninstall` / `restore` work on.
+#[derive(clap::Args, Debug, Default)]
+struct ScopeArgs {
+    /// user: ~/.claude/settings.json. project: <project>/.claude/settings.json
+    /// (shared with the repository). local: <project>/.claude/settings.local.json
+    #[arg(long, default_value_t)]
+    scope: Scope,
+    /// Project root for the project and local scopes [default: current
+    /// directory]
+    #[arg(long)]
+    project_dir: Option<std::path::PathBuf>,
+}
+
+impl ScopeArgs {
+    fn project_dir(&self) -> std::path::PathBuf {
+        self.project_dir
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+    }
+
+    fn path_of(&self, scope: Scope) -> std::path::PathBuf {
+        scope.settings_path(&home_dir(), &self.project_dir())
+    }
+
+    fn path(&self) -> std::path::PathBuf {
+        self.path_of(self.scope)
+    }
+}
+
+/// `--dry-run` / `--diff` for commands that rewrite settings.json.
+#[derive(clap::Args, Debug, Default)]
+struct PreviewArgs {
+    /// Print the hook entries that would be added, removed or changed, and
+    /// leave settings.json untouched
+    #[arg(long)]
+    dry_run: bool,
+    /// Print the hook changes, then write them
+    #[arg(long)]
+    diff: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct DecideArgs {
+    #[arg(long)]
+    url: String,
+    /// Body used when the hook payload has no tool description or name
+    #[arg(long)]
+    message: Option<String>,
+    #[arg(long)]
+    title: Option<String>,
+    #[arg(long)]
+    hostname: Option<String>,
+    /// Body template, as for `send --template`
+    #[arg(long)]
+    template: Option<String>,
+    /// Seconds to wait for a click before falling back to Claude's own
+    /// prompt. Keep it below the hook's timeout.
+    #[arg(long, default_value_t = DECIDE_TIMEOUT_SECS)]
+    timeout: u64,
+    #[arg(long, default_value_t = 2000)]
+    timeout_ms: u64,
+    #[arg(long)]
+    allow_label: Option<String>,
+    #[arg(long)]
+    deny_label: Option<String>,
+    #[arg(long)]
+    quiet: bool,
+    /// Marks a hook command as registered by agent-toast
+    /// (`hook_config::MARKER`); otherwise ignored.
+    #[arg(long = "agent-toast-managed", hide = true)]
+    _managed: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct BatchArgs {
+    #[arg(long)]
+    url: String,
+    /// JSON-lines file of notify requests; stdin when omitted
+    #[arg(long)]
+    file: Option<std::path::PathBuf>,
+    /// Host label for requests that don't carry one
+    #[arg(long)]
+    hostname: Option<String>,
+    #[arg(long, default_value_t = 5000)]
+    timeout_ms: u64,
+    #[arg(long)]
+    quiet: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct SchemaArgs {
+    #[arg(value_enum)]
+    target: SchemaTarget,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum SchemaTarget {
+    /// Body of `POST /notify` (and each item of `/notify/batch`)
+    Notify,
+    /// `settings.json`, describing the `agent_toast` block
+    Settings,
+}
+
+#[derive(clap::Args, Debug)]
+struct CapabilitiesArgs {
+    #[arg(long)]
+    url: String,
+    #[arg(long, default_value_t = 2000)]
+    timeout_ms: u64,
 }
 
 fn main() {
     let cli = Cli::parse();
     let exit = match cli.command {
         Some(Command::Init(args)) => run_init(args),
-        Some(Command::Uninstall) => run_uninstall(),
+        Some(Command::Uninstall(args)) => run_uninstall(args),
+        Some(Command::Restore(args)) => run_restore(args),
+        Some(Command::Capabilities(args)) => run_capabilities(args),
+        Some(Command::Schema(args)) => run_schema(args),
+        Some(Command::Decide(args)) => run_decide(args),
+        Some(Command::Batch(args)) => run_batch(args),
         Some(Command::Send(args)) => run_send(args),
         None => run_send(cli.send_args),
     };
     std::process::exit(exit);
 }
 
+/// This machine's host name, or `"unknown"`.
+fn local_hostname() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|s| s.into_string().ok())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".into())
+}
+
 fn build_request(args: &SendArgs) -> agent_toast_core::NotifyRequest {
-    let hostname_val = args
-        .hostname
-        .clone()
-        .or_else(|| {
-            hostname::get()
-                .ok()
-                .and_then(|s| s.into_string().ok())
-                .filter(|s| !s.is_empty())
-        })
-        .or_else(|| Some("unknown".into()));
+    let hostname_val = Some(args.hostname.clone().unwrap_or_else(local_hostname));
 
     let title_hint = args.title.clone().or_else(|| {
         std::env::var("CLAUDE_PROJECT_DIR")
@@ -109,6 +322,12 @@ fn build_request(args: &SendArgs) -> agent_toast_core::NotifyRequest {
         process_tree: None,
         source: args.source.clone(),
         hostname: hostname_val,
+        schema_version: agent_toast_core::wire::SCHEMA_VERSION,
+        dedupe_key: args.dedupe_key.clone().filter(|k| !k.is_empty()),
+        actions: args.actions.clone(),
+        priority: args.priority,
+        context: agent_toast_core::wire::HookContext::default().stamped(),
+        extra: args.fields.iter().cloned().collect(),
     }
 }
 
@@ -122,62 +341,480 @@ fn run_send(args: SendArgs) -> i32 {
             return 2;
         }
     };
-    if args.event.is_none() || args.event.as_deref() == Some("") {
+    let redactor = agent_toast_core::dynamic::Redactor::from_settings_file(settings_path());
+    if let Some(json) = &args.codex {
+        if let Err(e) = agent_toast_core::codex_config::forward(&args.chain, json) {
+            if !args.quiet {
+                eprintln!("error: chained notify program: {e}");
+            }
+        }
+        let notification = match agent_toast_core::codex::CodexNotification::parse(json) {
+            Ok(n) => n,
+            Err(e) => {
+                if !args.quiet {
+                    eprintln!("error: invalid Codex JSON: {e}");
+                }
+                return 1;
+            }
+        };
+        // Event, body, title, dedupe key and context come from the payload;
+        // host, priority and --field rows from the command line as usual.
+        let base = build_request(&args);
+        let codex = notification.to_request(&redactor);
+        let req = agent_toast_core::NotifyRequest {
+            title_hint: args.title.clone().or(codex.title_hint),
+            dedupe_key: base.dedupe_key.or(codex.dedupe_key),
+            hostname: base.hostname,
+            priority: base.priority,
+            extra: base.extra,
+            ..codex
+        };
+        deliver(&url, req, args.timeout_ms, args.quiet);
+        return 0;
+    }
+    // Other agents' hooks: the source adapter reads their payload. Claude
+    // Code and Codex keep the --dynamic / --codex paths.
+    let adapted = agent_toast_core::source::adapter(&args.source)
+        .filter(|a| !matches!(a.source(), "claude" | agent_toast_core::codex::SOURCE))
+        .map(|adapter| {
+            let payload = if adapter.reads_payload() {
+                agent_toast_core::dynamic::read_stdin_json().unwrap_or_default()
+            } else {
+                serde_json::Value::Null
+            };
+            let adapted = adapter.adapt(&payload, &redactor);
+            (payload, adapted)
+        });
+    // Claude Code hooks: stdin carries the body (--dynamic / --template) and,
+    // when --event is omitted, the hook_event_name the event is derived from.
+    let explicit_event = args.event.clone().filter(|e| !e.is_empty());
+    let hook = if adapted.is_none()
+        && (args.dynamic || args.template.is_some() || explicit_event.is_none())
+    {
+        agent_toast_core::dynamic::read_stdin_json()
+    } else {
+        None
+    };
+    let event = explicit_event
+        .or_else(|| adapted.as_ref().and_then(|(_, a)| a.event.clone()))
+        .or_else(|| {
+            hook.as_ref()
+                .and_then(agent_toast_core::hook_config::event_from_hook)
+                .map(str::to_string)
+        });
+    let Some(event) = event else {
         if !args.quiet {
-            eprintln!("error: --event is required");
+            eprintln!("error: --event is required (or a hook payload naming its event on stdin)");
         }
         return 2;
-    }
+    };
 
     let mut req = build_request(&args);
-    // --dynamic: replace the static message with one derived from the hook's
-    // stdin JSON (shared logic with the desktop CLI via core).
-    if args.dynamic {
-        req.message = agent_toast_core::dynamic::resolve_from_stdin(args.message.as_deref());
+    req.event = event;
+    if let Some((payload, adapted)) = adapted {
+        req.message = match &args.template {
+            Some(tpl) => agent_toast_core::dynamic::resolve_template(
+                &payload,
+                tpl,
+                args.message.as_deref(),
+                &redactor,
+            ),
+            None => adapted.message.or(req.message),
+        };
+        req.title_hint = args.title.clone().or(adapted.title_hint).or(req.title_hint);
+        req.context = adapted.context.stamped();
+    } else if let Some(hook) = &hook {
+        // --template / --dynamic: replace the static message with one derived
+        // from the hook's stdin JSON (shared logic with the desktop CLI via
+        // core). Either way, attach the session/cwd context it carries.
+        if let Some(tpl) = &args.template {
+            req.message = agent_toast_core::dynamic::resolve_template(
+                hook,
+                tpl,
+                args.message.as_deref(),
+                &redactor,
+            );
+        } else if args.dynamic {
+            req.message = agent_toast_core::dynamic::resolve_message(
+                hook,
+                args.message.as_deref(),
+                &redactor,
+            );
+        }
+        req.attach_hook_context(hook);
+    }
+    // The body crosses the network in plain HTTP; mask secrets the agent
+    // echoed (or a hook command interpolated into --message).
+    req.message = req.message.map(|m| redactor.redact(&m));
+    let Some(reply) = deliver(&url, req, args.timeout_ms, args.quiet) else {
+        return 0;
+    };
+    if let Some(ticket) = reply.ticket {
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(args.action_timeout);
+        if let Some(action) = wait_for_action(&url, &ticket, args.timeout_ms, deadline) {
+            println!("{action}");
+        }
+    }
+    0
+}
+
+/// POST `req` to the desktop after negotiating optional fields. Returns the
+/// receiver's reply (empty for a plain `204`), or `None` when delivery failed;
+/// failures are only warned about so the hook is never blocked.
+fn deliver(
+    url: &str,
+    mut req: agent_toast_core::NotifyRequest,
+    timeout_ms: u64,
+    quiet: bool,
+) -> Option<agent_toast_core::wire::NotifyReply> {
+    // Only pay for the extra round trip when the payload uses optional fields
+    // an older desktop might not understand.
+    if !req.required_features().is_empty() {
+        match fetch_capabilities(url, timeout_ms) {
+            Ok(caps) => downgrade(&mut req, &caps, quiet),
+            Err(e) => {
+                if !quiet {
+                    eprintln!("warn: capability check failed: {e}");
+                }
+            }
+        }
     }
-    let body = match serde_json::to_vec(&req) {
+    post_notify(url, &req, timeout_ms, quiet)
+}
+
+/// Strip what `caps` doesn't understand, warning about it.
+fn downgrade(
+    req: &mut agent_toast_core::NotifyRequest,
+    caps: &agent_toast_core::wire::Capabilities,
+    quiet: bool,
+) {
+    let dropped = req.downgrade_for(caps);
+    if !dropped.is_empty() && !quiet {
+        eprintln!(
+            "warn: desktop (schema v{}) does not support {}; dropped",
+            caps.schema_version,
+            dropped.join(", ")
+        );
+    }
+}
+
+/// `POST /notify` as-is (no negotiation).
+fn post_notify(
+    url: &str,
+    req: &agent_toast_core::NotifyRequest,
+    timeout_ms: u64,
+    quiet: bool,
+) -> Option<agent_toast_core::wire::NotifyReply> {
+    let body = match serde_json::to_vec(req) {
         Ok(b) => b,
         Err(e) => {
-            if !args.quiet {
+            if !quiet {
                 eprintln!("error: serialize failed: {e}");
             }
-            return 0;
+            return None;
         }
     };
 
     let endpoint = format!("{}/notify", url.trim_end_matches('/'));
     let result = ureq::post(&endpoint)
         .set("Content-Type", "application/json")
-        .timeout(std::time::Duration::from_millis(args.timeout_ms))
+        .timeout(std::time::Duration::from_millis(timeout_ms))
         .send_bytes(&body);
 
     match result {
-        Ok(resp) if (200..300).contains(&resp.status()) => 0,
+        Ok(resp) if (200..300).contains(&resp.status()) => Some(
+            resp.into_string()
+                .ok()
+                .and_then(|b| serde_json::from_str(&b).ok())
+                .unwrap_or_default(),
+        ),
         Ok(resp) => {
-            if !args.quiet {
+            if !quiet {
                 eprintln!("warn: server returned HTTP {}", resp.status());
             }
-            0
+            None
         }
         Err(e) => {
-            if !args.quiet {
+            if !quiet {
                 eprintln!("warn: send failed: {e}");
             }
+            None
+        }
+    }
+}
+
+/// `batch`: deliver spooled requests (JSON lines) with one
+/// `POST /notify/batch` per [`MAX_BATCH_ITEMS`](agent_toast_core::wire::MAX_BATCH_ITEMS)
+/// chunk, or one `POST /notify` each for a desktop without the batch
+/// endpoint. Like `send`, delivery problems are reported but exit 0.
+fn run_batch(args: BatchArgs) -> i32 {
+    use agent_toast_core::wire::{Capabilities, DropReason, NotifyOutcome, MAX_BATCH_ITEMS};
+    use std::collections::BTreeMap;
+    use std::io::Read;
+
+    let input = match &args.file {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display())),
+        None => {
+            let mut buf = String::new();
+            std::io::stdin()
+                .read_to_string(&mut buf)
+                .map(|_| buf)
+                .map_err(|e| e.to_string())
+        }
+    };
+    let input = match input {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: read failed: {e}");
+            return 1;
+        }
+    };
+
+    let (mut requests, invalid) = parse_request_lines(&input);
+    if invalid > 0 && !args.quiet {
+        eprintln!("warn: skipped {invalid} invalid line(s)");
+    }
+    if requests.is_empty() {
+        return 0;
+    }
+    let host = args.hostname.clone().unwrap_or_else(local_hostname);
+    for req in &mut requests {
+        req.hostname.get_or_insert_with(|| host.clone());
+    }
+
+    // An unreachable desktop fails the sends below anyway; assume it is
+    // current rather than stripping fields for nothing.
+    let caps = fetch_capabilities(&args.url, args.timeout_ms).unwrap_or_else(|e| {
+        if !args.quiet {
+            eprintln!("warn: capability check failed: {e}");
+        }
+        Capabilities::current()
+    });
+    for req in &mut requests {
+        downgrade(req, &caps, args.quiet);
+    }
+
+    let (mut accepted, mut rejected, mut failed) = (0usize, 0usize, 0usize);
+    let mut dropped: BTreeMap<DropReason, usize> = BTreeMap::new();
+    if caps.supports("batch") {
+        let endpoint = format!("{}/notify/batch", args.url.trim_end_matches('/'));
+        for chunk in requests.chunks(MAX_BATCH_ITEMS) {
+            let outcomes = serde_json::to_vec(chunk)
+                .map_err(|e| e.to_string())
+                .and_then(|body| {
+                    ureq::post(&endpoint)
+                        .set("Content-Type", "application/json")
+                        .timeout(std::time::Duration::from_millis(args.timeout_ms))
+                        .send_bytes(&body)
+                        .map_err(|e| e.to_string())
+                })
+                .and_then(|resp| resp.into_string().map_err(|e| e.to_string()))
+                .and_then(|body| {
+                    serde_json::from_str::<Vec<NotifyOutcome>>(&body).map_err(|e| e.to_string())
+                });
+            match outcomes {
+                Ok(outcomes) => {
+                    for outcome in outcomes {
+                        match outcome {
+                            NotifyOutcome::Accepted { .. } => accepted += 1,
+                            NotifyOutcome::Dropped { reason } => {
+                                *dropped.entry(reason).or_default() += 1;
+                            }
+                            NotifyOutcome::Rejected { error } => {
+                                rejected += 1;
+                                if !args.quiet {
+                                    eprintln!("warn: desktop rejected a request: {error}");
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    failed += chunk.len();
+                    if !args.quiet {
+                        eprintln!("warn: batch send failed: {e}");
+                    }
+                }
+            }
+        }
+    } else {
+        for req in &requests {
+            match post_notify(&args.url, req, args.timeout_ms, args.quiet) {
+                Some(_) => accepted += 1,
+                None => failed += 1,
+            }
+        }
+    }
+
+    if !args.quiet {
+        for (reason, n) in &dropped {
+            let retry = if reason.is_retryable() {
+                ", retry later"
+            } else {
+                ""
+            };
+            eprintln!(
+                "warn: desktop dropped {n} request(s): {}{retry}",
+                reason.as_str()
+            );
+        }
+        let dropped: usize = dropped.values().sum();
+        eprintln!("sent {accepted}, dropped {dropped}, rejected {rejected}, failed {failed}");
+    }
+    0
+}
+
+/// Parse one [`NotifyRequest`](agent_toast_core::NotifyRequest) per
+/// non-blank line. Returns the requests and the number of invalid lines.
+fn parse_request_lines(input: &str) -> (Vec<agent_toast_core::NotifyRequest>, usize) {
+    let mut invalid = 0;
+    let requests = input
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).inspect_err(|_| invalid += 1).ok())
+        .collect();
+    (requests, invalid)
+}
+
+/// `decide`: show the permission request with Allow/Deny buttons and print
+/// the hook decision. Dismissal, timeout or any failure prints nothing, which
+/// leaves Claude Code's own prompt in charge. Always exits 0.
+fn run_decide(args: DecideArgs) -> i32 {
+    use agent_toast_core::permission::{decision_actions, request_message, Decision};
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(args.timeout);
+    let hook = agent_toast_core::dynamic::read_stdin_json().unwrap_or_default();
+    let redactor = agent_toast_core::dynamic::Redactor::from_settings_file(settings_path());
+    let (allow, deny) = match detect_locale().as_str() {
+        "ko" => ("허용", "거부"),
+        _ => ("Allow", "Deny"),
+    };
+    let send_args = SendArgs {
+        url: Some(args.url.clone()),
+        event: Some("user_input_required".into()),
+        message: match &args.template {
+            Some(tpl) => agent_toast_core::dynamic::resolve_template(
+                &hook,
+                tpl,
+                args.message.as_deref(),
+                &redactor,
+            ),
+            None => request_message(&hook, args.message.as_deref(), &redactor),
+        },
+        title: args.title,
+        hostname: args.hostname,
+        actions: decision_actions(
+            args.allow_label.as_deref().unwrap_or(allow),
+            args.deny_label.as_deref().unwrap_or(deny),
+        ),
+        source: "claude".into(),
+        timeout_ms: args.timeout_ms,
+        quiet: args.quiet,
+        ..SendArgs::default()
+    };
+    let mut req = build_request(&send_args);
+    req.attach_hook_context(&hook);
+
+    let action = deliver(&args.url, req, args.timeout_ms, args.quiet)
+        .and_then(|reply| reply.ticket)
+        .and_then(|ticket| wait_for_action(&args.url, &ticket, args.timeout_ms, deadline));
+    if let Some(output) = Decision::from_action(action.as_deref()).hook_output() {
+        println!("{output}");
+    }
+    0
+}
+
+/// Poll `GET /actions/<ticket>` until the toast settles or `deadline` passes.
+/// Returns the clicked action id; dismissals, expiry and errors yield `None`.
+fn wait_for_action(
+    url: &str,
+    ticket: &str,
+    timeout_ms: u64,
+    deadline: std::time::Instant,
+) -> Option<String> {
+    use agent_toast_core::wire::ActionResult;
+
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+    let endpoint = format!("{}/actions/{}", url.trim_end_matches('/'), ticket);
+    loop {
+        let result = ureq::get(&endpoint)
+            .timeout(std::time::Duration::from_millis(timeout_ms))
+            .call()
+            .ok()
+            .and_then(|resp| resp.into_string().ok())
+            .and_then(|body| serde_json::from_str::<ActionResult>(&body).ok())
+            // 404 (ticket expired / desktop restarted) and transport errors end the wait
+            .unwrap_or(ActionResult::Unknown);
+        match result {
+            ActionResult::Clicked { action } => return Some(action),
+            ActionResult::Pending if std::time::Instant::now() + POLL_INTERVAL < deadline => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Ask the desktop what it understands. A receiver that predates the
+/// `/capabilities` endpoint answers 404 and is treated as
+/// [`Capabilities::legacy`](agent_toast_core::wire::Capabilities::legacy).
+fn fetch_capabilities(
+    url: &str,
+    timeout_ms: u64,
+) -> Result<agent_toast_core::wire::Capabilities, String> {
+    use agent_toast_core::wire::Capabilities;
+
+    let endpoint = format!("{}/capabilities", url.trim_end_matches('/'));
+    let result = ureq::get(&endpoint)
+        .timeout(std::time::Duration::from_millis(timeout_ms))
+        .call();
+    match result {
+        Ok(resp) => {
+            let body = resp.into_string().map_err(|e| e.to_string())?;
+            serde_json::from_str(&body).map_err(|e| e.to_string())
+        }
+        Err(ureq::Error::Status(404, _)) => Ok(Capabilities::legacy()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn run_capabilities(args: CapabilitiesArgs) -> i32 {
+    match fetch_capabilities(&args.url, args.timeout_ms) {
+        Ok(caps) => {
+            println!("{}", serde_json::to_string_pretty(&caps).unwrap());
             0
         }
+        Err(e) => {
+            eprintln!("error: {e}");
+            1
+        }
     }
 }
 
-pub fn settings_path() -> std::path::PathBuf {
+fn run_schema(args: SchemaArgs) -> i32 {
+    let schema = match args.target {
+        SchemaTarget::Notify => agent_toast_core::schema::notify_request(),
+        SchemaTarget::Settings => agent_toast_core::schema::settings_file(),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+    0
+}
+
+fn home_dir() -> std::path::PathBuf {
     // On Linux: $HOME is respected. On Windows: dirs uses SHGetKnownFolderPath
     // (ignores %USERPROFILE% env override), so we check the env var first to
     // allow integration tests to redirect the settings file via HOME/USERPROFILE.
-    let home = std::env::var_os("HOME")
+    std::env::var_os("HOME")
         .or_else(|| std::env::var_os("USERPROFILE"))
         .map(std::path::PathBuf::from)
         .or_else(dirs::home_dir)
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
-    home.join(".claude").join("settings.json")
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+}
+
+/// The user-level `~/.claude/settings.json`.
+pub fn settings_path() -> std::path::PathBuf {
+    Scope::User.settings_path(&home_dir(), std::path::Path::new("."))
 }
 
 fn detect_locale() -> String {
@@ -194,18 +831,24 @@ fn detect_locale() -> String {
 }
 
 fn run_init(args: InitArgs) -> i32 {
-    use agent_toast_core::hook_config::{merge_agent_toast_hooks, HookEntry};
+    use agent_toast_core::backup::write_with_backup;
+    use agent_toast_core::hook_config::{
+        event_for, merge_agent_toast_hooks, waiting_timeout, HookEntry,
+    };
+    use agent_toast_core::json_edit::rewrite;
 
-    let path = settings_path();
-    let root: serde_json::Value = match std::fs::read_to_string(&path) {
-        Ok(s) if !s.trim().is_empty() => match serde_json::from_str(&s) {
+    let path = args.scope.path();
+    let original = std::fs::read_to_string(&path).unwrap_or_default();
+    let root: serde_json::Value = if original.trim().is_empty() {
+        serde_json::json!({})
+    } else {
+        match serde_json::from_str(&original) {
             Ok(v) => v,
             Err(e) => {
                 eprintln!("error: invalid JSON in {}: {e}", path.display());
                 return 1;
             }
-        },
-        _ => serde_json::json!({}),
+        }
     };
 
     let host_flag = args
@@ -224,44 +867,70 @@ fn run_init(args: InitArgs) -> i32 {
 
     let dyn_flag = if args.dynamic { " --dynamic" } else { "" };
     let stop_cmd = format!(
-        "agent-toast-send --url {} --event task_complete --message {}{}{}",
+        "agent-toast-send --url {} --event {} --message {}{}{}",
         url_esc,
+        event_for("Stop", None).unwrap_or_default(),
         shell_escape::escape(stop_msg.into()),
         host_flag,
         dyn_flag,
     );
     let input_cmd = format!(
-        "agent-toast-send --url {} --event user_input_required --message {}{}{}",
+        "agent-toast-send --url {} --event {} --message {}{}{}",
         url_esc,
+        event_for("Notification", Some("permission_prompt")).unwrap_or_default(),
         shell_escape::escape(input_msg.into()),
         host_flag,
         dyn_flag,
     );
 
-    let entries = vec![
+    // 0 leaves Claude Code's own default.
+    let timeout = (args.hook_timeout > 0).then_some(args.hook_timeout);
+
+    // With --decide the permission prompt is answered from the toast, so the
+    // plain permission_prompt notification would only duplicate it.
+    let input_entry = if args.decide {
         HookEntry {
-            event_key: "Stop",
+            event_key: "PermissionRequest",
             matcher: None,
-            command: stop_cmd,
-        },
+            command: format!(
+                "agent-toast-send decide --url {} --message {}{}",
+                url_esc,
+                shell_escape::escape(input_msg.into()),
+                host_flag,
+            ),
+            timeout: timeout.map(|t| waiting_timeout(DECIDE_TIMEOUT_SECS, t)),
+            run_async: false,
+        }
+    } else {
         HookEntry {
             event_key: "Notification",
             matcher: Some("permission_prompt"),
             command: input_cmd,
+            timeout,
+            run_async: args.run_async,
+        }
+    };
+    let registered = format!("Stop, {}", input_entry.event_key);
+    let entries = vec![
+        HookEntry {
+            event_key: "Stop",
+            matcher: None,
+            command: stop_cmd,
+            timeout,
+            run_async: args.run_async,
         },
+        input_entry,
     ];
 
-    let merged = merge_agent_toast_hooks(root, &entries);
-
-    if let Some(parent) = path.parent() {
-        if let Err(e) = std::fs::create_dir_all(parent) {
-            eprintln!("error: create dir {}: {e}", parent.display());
-            return 1;
-        }
+    let merged = merge_agent_toast_hooks(root.clone(), &entries);
+    warn_duplicates(&args.scope, &merged);
+    if preview_hooks(&args.preview, &root, &merged, &path) {
+        return 0;
     }
-    match std::fs::write(&path, serde_json::to_string_pretty(&merged).unwrap()) {
+
+    match write_with_backup(&home_dir(), &path, rewrite(&original, &merged)) {
         Ok(()) => {
-            eprintln!("registered: Stop, Notification → {}", path.display());
+            eprintln!("registered: {registered} → {}", path.display());
             0
         }
         Err(e) => {
@@ -271,10 +940,12 @@ fn run_init(args: InitArgs) -> i32 {
     }
 }
 
-fn run_uninstall() -> i32 {
+fn run_uninstall(args: UninstallArgs) -> i32 {
+    use agent_toast_core::backup::write_with_backup;
     use agent_toast_core::hook_config::remove_agent_toast_hooks;
+    use agent_toast_core::json_edit::rewrite;
 
-    let path = settings_path();
+    let path = args.scope.path();
     let Ok(s) = std::fs::read_to_string(&path) else {
         eprintln!("nothing to uninstall (no {})", path.display());
         return 0;
@@ -286,8 +957,12 @@ fn run_uninstall() -> i32 {
             return 1;
         }
     };
-    let cleaned = remove_agent_toast_hooks(root);
-    match std::fs::write(&path, serde_json::to_string_pretty(&cleaned).unwrap()) {
+    let cleaned = remove_agent_toast_hooks(root.clone());
+    warn_duplicates(&args.scope, &cleaned);
+    if preview_hooks(&args.preview, &root, &cleaned, &path) {
+        return 0;
+    }
+    match write_with_backup(&home_dir(), &path, rewrite(&s, &cleaned)) {
         Ok(()) => {
             eprintln!("removed agent-toast hooks from {}", path.display());
             0
@@ -299,6 +974,90 @@ fn run_uninstall() -> i32 {
     }
 }
 
+fn run_restore(args: RestoreArgs) -> i32 {
+    use agent_toast_core::backup;
+
+    let path = args.scope.path();
+    let backups = backup::list(&home_dir(), &path);
+    let Some(index) = args.index else {
+        if backups.is_empty() {
+            eprintln!("no backups of {}", path.display());
+        }
+        for (i, b) in backups.iter().enumerate() {
+            println!("{:>2}  {}  {}", i + 1, b.created, b.path.display());
+        }
+        return 0;
+    };
+    let Some(chosen) = index.checked_sub(1).and_then(|i| backups.get(i)) else {
+        eprintln!(
+            "error: no backup #{index} of {} ({} available)",
+            path.display(),
+            backups.len()
+        );
+        return 1;
+    };
+    match backup::restore(&home_dir(), &path, &chosen.path) {
+        Ok(()) => {
+            eprintln!("restored {} from {}", path.display(), chosen.created);
+            0
+        }
+        Err(e) => {
+            eprintln!("error: restore {}: {e}", path.display());
+            1
+        }
+    }
+}
+
+/// Warn on stderr about agent-toast hooks registered in more than one scope,
+/// with `written` standing in for the settings of the scope being changed.
+///
+/// Run from the home directory, the project and user files are the same
+/// file; it is only read once.
+fn warn_duplicates(scope: &ScopeArgs, written: &serde_json::Value) {
+    let mut paths = vec![scope.path()];
+    let others: Vec<(Scope, serde_json::Value)> = Scope::ALL
+        .into_iter()
+        .filter_map(|s| {
+            let path = scope.path_of(s);
+            if paths.contains(&path) {
+                return None;
+            }
+            paths.push(path.clone());
+            let content = std::fs::read_to_string(path).ok()?;
+            Some((s, serde_json::from_str(&content).ok()?))
+        })
+        .collect();
+    let mut roots: Vec<(Scope, &serde_json::Value)> =
+        others.iter().map(|(s, root)| (*s, root)).collect();
+    roots.push((scope.scope, written));
+    for dup in agent_toast_core::hook_config::duplicate_hooks(&roots) {
+        eprintln!("warning: {dup}");
+    }
+}
+
+/// For `--dry-run` / `--diff`: print the hook changes from `before` to
+/// `after` on stdout. Returns `true` when nothing should be written.
+fn preview_hooks(
+    preview: &PreviewArgs,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    path: &std::path::Path,
+) -> bool {
+    if !preview.dry_run && !preview.diff {
+        return false;
+    }
+    let diff = agent_toast_core::hook_config::diff_hooks(before, after);
+    if diff.is_empty() {
+        eprintln!("no hook changes for {}", path.display());
+    } else {
+        print!("{diff}");
+    }
+    if preview.dry_run {
+        eprintln!("dry run: {} not written", path.display());
+    }
+    preview.dry_run
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,7 +1086,16 @@ mod tests {
             source: "claude".into(),
             timeout_ms: 2000,
             quiet: false,
+            _managed: false,
             dynamic: false,
+            codex: None,
+            chain: vec![],
+            template: None,
+            dedupe_key: Some("turn-1".into()),
+            actions: vec!["allow=Allow".parse().unwrap()],
+            action_timeout: 120,
+            priority: agent_toast_core::wire::Priority::High,
+            fields: vec![("branch".into(), "main".into())],
         };
         let req = build_request(&args);
         assert_eq!(req.event, "task_complete");
@@ -337,6 +1105,19 @@ mod tests {
         assert_eq!(req.pid, 0);
         assert!(req.process_tree.is_none());
         assert_eq!(req.source, "claude");
+        assert_eq!(req.dedupe_key.as_deref(), Some("turn-1"));
+        assert_eq!(req.actions[0].id, "allow");
+        assert_eq!(req.priority, agent_toast_core::wire::Priority::High);
+        assert_eq!(req.extra["branch"], "main");
+    }
+
+    #[test]
+    fn payload_empty_dedupe_key_is_none() {
+        let args = SendArgs {
+            dedupe_key: Some(String::new()),
+            ..mk_args("http://x", "test")
+        };
+        assert!(build_request(&args).dedupe_key.is_none());
     }
 
     #[test]
@@ -114,3 +114,331 @@ fn init_without_dynamic_has_no_flag() {
         "--dynamic 없이 init 하면 플래그가 없어야 함"
     );
 }
+
+#[test]
+fn init_decide_registers_permission_request_instead_of_prompt() {
+    let tmp = tempfile::tempdir().unwrap();
+    let status = run_with_home(
+        tmp.path(),
+        &["init", "--url", "http://desktop:8787", "--decide"],
+    );
+    assert!(status.success());
+
+    let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
+    let v: serde_json::Value = serde_json::from_str(&content).unwrap();
+    let cmd = v["hooks"]["PermissionRequest"][0]["hooks"][0]["command"]
+        .as_str()
+        .unwrap();
+    assert!(cmd.starts_with("agent-toast-send decide --url"));
+    assert!(
+        v["hooks"]["Notification"].is_null(),
+        "decide 모드에서는 permission_prompt 알림을 중복 등록하지 않음"
+    );
+}
+
+#[test]
+fn init_sets_hook_timeout_and_async() {
+    let read = |home: &std::path::Path| -> serde_json::Value {
+        serde_json::from_str(&fs::read_to_string(home.join(".claude/settings.json")).unwrap())
+            .unwrap()
+    };
+
+    let tmp = tempfile::tempdir().unwrap();
+    assert!(run_with_home(tmp.path(), &["init", "--url", "http://desktop:8787"]).success());
+    let v = read(tmp.path());
+    let stop = &v["hooks"]["Stop"][0]["hooks"][0];
+    assert_eq!(
+        stop["timeout"], 10,
+        "느린 전송이 Stop 훅을 붙잡지 않도록 기본 10초"
+    );
+    assert!(stop.get("async").is_none());
+
+    let tmp = tempfile::tempdir().unwrap();
+    let args = [
+        "init",
+        "--url",
+        "http://desktop:8787",
+        "--decide",
+        "--async",
+        "--hook-timeout",
+        "5",
+    ];
+    assert!(run_with_home(tmp.path(), &args).success());
+    let v = read(tmp.path());
+    let stop = &v["hooks"]["Stop"][0]["hooks"][0];
+    assert_eq!(stop["timeout"], 5);
+    assert_eq!(stop["async"], true);
+    let decide = &v["hooks"]["PermissionRequest"][0]["hooks"][0];
+    assert_eq!(decide["timeout"], 55, "decide 대기 시간(50초) + 전송 시간");
+    assert!(
+        decide.get("async").is_none(),
+        "decide 응답은 Claude가 읽어야 함"
+    );
+
+    let tmp = tempfile::tempdir().unwrap();
+    let args = [
+        "init",
+        "--url",
+        "http://desktop:8787",
+        "--hook-timeout",
+        "0",
+    ];
+    assert!(run_with_home(tmp.path(), &args).success());
+    assert!(read(tmp.path())["hooks"]["Stop"][0]["hooks"][0]
+        .get("timeout")
+        .is_none());
+}
+
+fn output_with_home(home: &std::path::Path, args: &[&str]) -> std::process::Output {
+    let exe = env!("CARGO_BIN_EXE_agent-toast-send");
+    std::process::Command::new(exe)
+        .args(args)
+        .env("HOME", home)
+        .env("USERPROFILE", home) // Windows
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn init_dry_run_prints_diff_and_writes_nothing() {
+    let tmp = tempfile::tempdir().unwrap();
+    let settings_path = tmp.path().join(".claude/settings.json");
+    fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+    let original = r#"{"hooks":{"Stop":[{"hooks":[{"type":"command","command":"agent-toast-send --event old"}]}]}}"#;
+    fs::write(&settings_path, original).unwrap();
+
+    let out = output_with_home(
+        tmp.path(),
+        &["init", "--url", "http://desktop:8787", "--dry-run"],
+    );
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Notification [permission_prompt]:\n  + agent-toast-send"));
+    assert!(
+        stdout.contains("Stop:\n  ~ agent-toast-send --event old\n    → agent-toast-send --url")
+    );
+    assert_eq!(
+        fs::read_to_string(&settings_path).unwrap(),
+        original,
+        "dry run은 파일을 건드리지 않아야 함"
+    );
+}
+
+#[test]
+fn uninstall_diff_prints_removals_and_writes() {
+    let tmp = tempfile::tempdir().unwrap();
+    run_with_home(tmp.path(), &["init", "--url", "http://desktop:8787"]);
+
+    let out = output_with_home(tmp.path(), &["uninstall", "--diff"]);
+    assert!(out.status.success());
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Stop:\n  - agent-toast-send"));
+    assert!(stdout.contains("  - agent-toast-send --url"));
+
+    let content = fs::read_to_string(tmp.path().join(".claude/settings.json")).unwrap();
+    assert!(
+        !content.contains("agent-toast-send"),
+        "--diff는 출력 후 저장"
+    );
+
+    // Nothing left to remove: no diff lines.
+    let out = output_with_home(tmp.path(), &["uninstall", "--dry-run"]);
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no hook changes"));
+}
+
+#[test]
+fn init_and_uninstall_keep_unrelated_formatting() {
+    let tmp = tempfile::tempdir().unwrap();
+    let settings_path = tmp.path().join(".claude/settings.json");
+    fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+    let original = "{\n    \"permissions\": { \"allow\": [\"Bash(ls:*)\"] },\n    \"model\": \"opus\",\n    \"hooks\": {\n        \"Stop\": [ {\"hooks\": [{\"type\": \"command\", \"command\": \"/usr/bin/custom\"}]} ]\n    }\n}\n";
+    fs::write(&settings_path, original).unwrap();
+
+    assert!(run_with_home(tmp.path(), &["init", "--url", "http://desktop:8787"]).success());
+    let content = fs::read_to_string(&settings_path).unwrap();
+    assert!(
+        content.starts_with(
+            "{\n    \"permissions\": { \"allow\": [\"Bash(ls:*)\"] },\n    \"model\": \"opus\",\n    \"hooks\": {\n"
+        ),
+        "키 순서와 서식 보존: {content}"
+    );
+    assert!(content.contains(r#"{"hooks": [{"type": "command", "command": "/usr/bin/custom"}]}"#));
+    assert!(
+        content.contains("\n            {\n                \"matcher\": \"permission_prompt\",\n                \"hooks\": [\n"),
+        "4칸 들여쓰기 유지"
+    );
+
+    assert!(run_with_home(tmp.path(), &["uninstall"]).success());
+    let content = fs::read_to_string(&settings_path).unwrap();
+    assert!(!content.contains("agent-toast-send"));
+    assert!(content.starts_with("{\n    \"permissions\": { \"allow\": [\"Bash(ls:*)\"] },"));
+}
+
+#[test]
+fn init_backs_up_and_restore_rolls_back() {
+    let tmp = tempfile::tempdir().unwrap();
+    let settings_path = tmp.path().join(".claude/settings.json");
+    fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+    let original = r#"{"hooks":{"Stop":[{"hooks":[{"type":"command","command":"/usr/bin/my-custom-hook"}]}]}}"#;
+    fs::write(&settings_path, original).unwrap();
+
+    assert!(run_with_home(tmp.path(), &["init", "--url", "http://desktop:8787"]).success());
+    assert!(run_with_home(tmp.path(), &["uninstall"]).success());
+
+    let out = output_with_home(tmp.path(), &["restore"]);
+    assert!(out.status.success());
+    let listing = String::from_utf8_lossy(&out.stdout);
+    assert_eq!(
+        listing.lines().count(),
+        2,
+        "init/uninstall 각각 백업: {listing}"
+    );
+    assert!(listing.starts_with(" 1  "));
+    let backups = tmp.path().join(".claude").join("agent-toast-backups");
+    assert!(listing.contains(&*backups.to_string_lossy()), "{listing}");
+
+    // #2 is the file as it was before init.
+    assert!(run_with_home(tmp.path(), &["restore", "2"]).success());
+    assert_eq!(fs::read_to_string(&settings_path).unwrap(), original);
+
+    let out = output_with_home(tmp.path(), &["restore", "9"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no backup #9"));
+}
+
+#[test]
+fn restore_without_backups_lists_nothing() {
+    let tmp = tempfile::tempdir().unwrap();
+    let out = output_with_home(tmp.path(), &["restore"]);
+    assert!(out.status.success());
+    assert!(out.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("no backups"));
+}
+
+#[test]
+fn init_project_and_local_scopes_write_project_files() {
+    let home = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    let project_dir = project.path().to_str().unwrap();
+
+    let out = output_with_home(
+        home.path(),
+        &[
+            "init",
+            "--url",
+            "http://desktop:8787",
+            "--scope",
+            "project",
+            "--project-dir",
+            project_dir,
+        ],
+    );
+    assert!(out.status.success());
+    let shared = fs::read_to_string(project.path().join(".claude/settings.json")).unwrap();
+    assert!(shared.contains("agent-toast-send"));
+    assert!(
+        !home.path().join(".claude/settings.json").exists(),
+        "user 설정은 건드리지 않음"
+    );
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("warning"));
+
+    // Without --project-dir the current directory is the project.
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_agent-toast-send"))
+        .args(["init", "--url", "http://desktop:8787", "--scope", "local"])
+        .env("HOME", home.path())
+        .env("USERPROFILE", home.path())
+        .current_dir(project.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let local = fs::read_to_string(project.path().join(".claude/settings.local.json")).unwrap();
+    assert!(local.contains("agent-toast-send"));
+
+    assert!(run_with_home(
+        home.path(),
+        &[
+            "uninstall",
+            "--scope",
+            "project",
+            "--project-dir",
+            project_dir
+        ]
+    )
+    .success());
+    let shared = fs::read_to_string(project.path().join(".claude/settings.json")).unwrap();
+    assert!(!shared.contains("agent-toast-send"));
+    let local = fs::read_to_string(project.path().join(".claude/settings.local.json")).unwrap();
+    assert!(local.contains("agent-toast-send"), "local 범위는 그대로");
+
+    // 프로젝트 파일의 백업도 저장소가 아닌 홈 아래에 둔다
+    assert!(!project.path().join(".claude/agent-toast-backups").exists());
+    assert!(home.path().join(".claude/agent-toast-backups").is_dir());
+}
+
+#[test]
+fn init_warns_about_hooks_in_several_scopes() {
+    let home = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    let project_dir = project.path().to_str().unwrap();
+    assert!(run_with_home(home.path(), &["init", "--url", "http://desktop:8787"]).success());
+
+    let out = output_with_home(
+        home.path(),
+        &[
+            "init",
+            "--url",
+            "http://desktop:8787",
+            "--scope",
+            "local",
+            "--project-dir",
+            project_dir,
+            "--dry-run",
+        ],
+    );
+    assert!(out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("warning: Stop is registered in user and local settings"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("warning: Notification [permission_prompt] is registered"));
+    assert!(!project.path().join(".claude").exists(), "dry run");
+}
+
+#[test]
+fn uninstall_keeps_wrapper_scripts_named_after_agent_toast() {
+    let tmp = tempfile::tempdir().unwrap();
+    let settings_path = tmp.path().join(".claude/settings.json");
+    fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+    fs::write(
+        &settings_path,
+        r#"{"hooks":{"Stop":[
+            {"hooks":[{"type":"command","command":"/home/me/agent-toast-wrapper.sh"}]},
+            {"hooks":[{"type":"command","command":"agent-toast-send --url http://old --event task_complete"}]}
+        ]}}"#,
+    )
+    .unwrap();
+
+    // init marks what it writes and migrates the legacy entry it replaces.
+    assert!(run_with_home(tmp.path(), &["init", "--url", "http://desktop:8787"]).success());
+    let v: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&settings_path).unwrap()).unwrap();
+    let stop = v["hooks"]["Stop"].as_array().unwrap();
+    assert_eq!(stop.len(), 2, "{stop:?}");
+    assert_eq!(
+        stop[0]["hooks"][0]["command"],
+        "/home/me/agent-toast-wrapper.sh"
+    );
+    assert!(stop[1]["hooks"][0]["command"]
+        .as_str()
+        .unwrap()
+        .ends_with(" --agent-toast-managed"));
+
+    assert!(run_with_home(tmp.path(), &["uninstall"]).success());
+    let content = fs::read_to_string(&settings_path).unwrap();
+    assert!(content.contains("/home/me/agent-toast-wrapper.sh"));
+    assert!(!content.contains("agent-toast-send"));
+}
@@ -9,6 +9,16 @@ export interface NotificationData {
   source: string;
   hostname: string | null;
   show_hostname: boolean;
+  actions: ToastAction[];
+  session_id: string | null;
+  cwd: string | null;
+  /** [key, value] rows shown under the message */
+  extra: [string, string][];
+}
+
+export interface ToastAction {
+  id: string;
+  label: string;
 }
 
 export interface HookConfig {
@@ -45,6 +55,7 @@ export interface HookConfig {
   title_display_mode: string;
   auto_close_on_focus: boolean;
   auto_dismiss_seconds: number;
+  dedupe_window_seconds: number;
   notification_position: string;
   notification_sound: boolean;
   notification_sound_file: string | null;
@@ -56,6 +67,11 @@ export interface HookConfig {
   http_port: number;
   show_hostname: boolean;
   dynamic_message_enabled: boolean;
+  permission_decide_enabled: boolean;
+  /** Hook timeout in seconds (0 = Claude Code's default); decide hooks add their wait */
+  hook_timeout_seconds: number;
+  /** Run notification hooks in the background ("async": true), except decide */
+  hook_async: boolean;
   toast_bar: string;
   toast_border: string;
   toast_effects: string[];
@@ -63,6 +79,20 @@ export interface HookConfig {
   toast_density: string;
   toast_font_sans: string;
   toast_font_mono: string;
+  templates: Record<string, string>;
+  /** settings.json the hooks go to; the app settings always stay in ~/.claude */
+  hook_scope: HookScope;
+  /** Project root for the "project" and "local" scopes */
+  project_dir: string;
+}
+
+export type HookScope = "user" | "project" | "local";
+
+/** agent-toast hook registered in more than one scope */
+export interface DuplicateHook {
+  event_key: string;
+  matcher: string | null;
+  scopes: HookScope[];
 }
 
 export interface MonitorInfo {
@@ -79,6 +109,7 @@ export interface CounterSet {
   closed_focus: number;
   skipped_focused: number;
   skipped_ratelimit: number;
+  skipped_duplicate: number;
 }
 
 export interface Stats {
@@ -122,3 +153,26 @@ export interface ToastStyle {
   /** 코드 폰트 패밀리명. "" = 기본(번들) */
   font_mono: string;
 }
+
+/** Hook command change reported by `preview_hook_config` */
+export type HookChange =
+  | { kind: "added"; command: string }
+  | { kind: "removed"; command: string }
+  | { kind: "changed"; before: string; after: string };
+
+export type HookDiffEntry = HookChange & {
+  event_key: string;
+  matcher: string | null;
+};
+
+export interface HookDiff {
+  entries: HookDiffEntry[];
+}
+
+/** Backup of a file agent-toast writes ("claude" = ~/.claude/settings.json, "codex" = config.toml, "project"/"local" = the hook scope's file) */
+export interface SettingsBackup {
+  file: "claude" | "codex" | "project" | "local";
+  path: string;
+  /** UTC, RFC 3339 */
+  created: string;
+}
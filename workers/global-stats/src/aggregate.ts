@@ -8,6 +8,7 @@ export const COUNTER_KEYS = [
   "closed_focus",
   "skipped_focused",
   "skipped_ratelimit",
+  "skipped_duplicate",
 ] as const;
 
 const THIRTY_DAYS_MS = 30 * 24 * 60 * 60 * 1000;